@@ -0,0 +1,117 @@
+//! http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time
+//!
+
+use super::{error::RtpError, extmap::ExtValue, NtpTime};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |  ID   | len=7 |     absolute capture timestamp (bit 0-23)     |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |             absolute capture timestamp (bit 24-55)            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |  ... (56-63)  |
+   +-+-+-+-+-+-+-+-+
+
+    optional: 64 bits estimated capture clock offset follows
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AbsCaptureTimeValue {
+    /// Capture time in sender's NTP clock
+    pub capture_time: NtpTime,
+    /// Q32.32 signed seconds, sender's estimate of offset between its clock and capturer's
+    pub clock_offset: Option<i64>,
+}
+
+impl AbsCaptureTimeValue {
+    pub const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time";
+
+    pub const MIN_LEN: usize = 8;
+
+    pub const MAX_LEN: usize = 16;
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.len() < Self::MIN_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::MIN_LEN,
+                actual: data.len(),
+                origin: "Abs capture time value length",
+            });
+        }
+
+        let capture_time = NtpTime(u64::from_be_bytes(data[..8].try_into().unwrap()));
+
+        let clock_offset = if data.len() >= Self::MAX_LEN {
+            Some(i64::from_be_bytes(data[8..16].try_into().unwrap()))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            capture_time,
+            clock_offset,
+        })
+    }
+
+    #[inline]
+    pub fn encoded_len(&self) -> usize {
+        if self.clock_offset.is_some() {
+            Self::MAX_LEN
+        } else {
+            Self::MIN_LEN
+        }
+    }
+
+    /// Only the first encoded_len bytes are meaningful, the rest are zero
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0_u8; Self::MAX_LEN];
+        bytes[..8].copy_from_slice(&self.capture_time.0.to_be_bytes());
+        if let Some(offset) = self.clock_offset {
+            bytes[8..].copy_from_slice(&offset.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl<'a> ExtValue<'a> for AbsCaptureTimeValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::NtpTime;
+
+    use super::AbsCaptureTimeValue;
+
+    #[test]
+    fn test() {
+        let with_offset = AbsCaptureTimeValue {
+            capture_time: NtpTime(0x0102_0304_0506_0708),
+            clock_offset: Some(-(1 << 31)),
+        };
+        let bytes = with_offset.to_bytes();
+        assert_eq!(with_offset.encoded_len(), 16);
+        assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8, 0xFF, 0xFF, 0xFF, 0xFF, 0x80, 0, 0, 0]);
+        assert_eq!(AbsCaptureTimeValue::parse(&bytes).unwrap(), with_offset);
+
+        let no_offset = AbsCaptureTimeValue {
+            capture_time: NtpTime(0x0102_0304_0506_0708),
+            clock_offset: None,
+        };
+        let bytes = no_offset.to_bytes();
+        assert_eq!(no_offset.encoded_len(), 8);
+        assert_eq!(bytes[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(AbsCaptureTimeValue::parse(&bytes[..8]).unwrap(), no_offset);
+        // offset is only read from a full 16 bytes value
+        assert_eq!(AbsCaptureTimeValue::parse(&bytes[..12]).unwrap(), no_offset);
+        assert!(AbsCaptureTimeValue::parse(&bytes[..7]).is_err());
+    }
+}
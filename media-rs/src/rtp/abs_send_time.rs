@@ -0,0 +1,87 @@
+//! http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time
+//!
+
+use std::time::Duration;
+
+use super::{error::RtpError, extmap::ExtValue};
+
+
+/// 24 bits, 6.18 fixed point seconds, wraps around every 64 seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AbsSendTimeValue(pub u32);
+
+impl AbsSendTimeValue {
+    pub const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
+
+    pub const LEN: usize = 3;
+
+    const FRACTION_BITS: u32 = 18;
+    const MASK: u32 = 0x00FF_FFFF;
+    const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.len() < Self::LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::LEN,
+                actual: data.len(),
+                origin: "Abs send time value length",
+            });
+        }
+
+        Ok(Self(u32::from_be_bytes([0, data[0], data[1], data[2]])))
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 3] {
+        let b = self.0.to_be_bytes();
+        [b[1], b[2], b[3]]
+    }
+
+    /// Only the lower 64 seconds of duration are kept
+    pub fn from_duration(d: Duration) -> Self {
+        let fixed = ((d.as_nanos() << Self::FRACTION_BITS) / Self::NANOS_PER_SEC as u128) as u64;
+        Self(fixed as u32 & Self::MASK)
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        let nanos = ((self.0 & Self::MASK) as u64 * Self::NANOS_PER_SEC) >> Self::FRACTION_BITS;
+        Duration::from_nanos(nanos)
+    }
+
+    /// Elapsed time from rhs to self, assuming less than 64 seconds
+    pub fn wrapping_sub(&self, rhs: Self) -> Duration {
+        Self(self.0.wrapping_sub(rhs.0) & Self::MASK).to_duration()
+    }
+}
+
+
+impl<'a> ExtValue<'a> for AbsSendTimeValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::AbsSendTimeValue;
+
+    #[test]
+    fn test() {
+        let v = AbsSendTimeValue::from_duration(Duration::from_millis(1500));
+        assert_eq!(v.0, 0x06_0000);
+        assert_eq!(v.to_bytes(), [0x06, 0x00, 0x00]);
+        assert_eq!(AbsSendTimeValue::parse(&v.to_bytes()).unwrap(), v);
+        assert_eq!(v.to_duration(), Duration::from_millis(1500));
+
+        let wrapped = AbsSendTimeValue::from_duration(Duration::from_millis(64_000 + 250));
+        assert_eq!(wrapped.to_duration(), Duration::from_millis(250));
+        assert_eq!(wrapped.wrapping_sub(AbsSendTimeValue::from_duration(Duration::from_millis(63_750))), Duration::from_millis(500));
+
+        assert!(AbsSendTimeValue::parse(&[1, 2]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use super::error::RtpError;
+use super::{error::RtpError, extmap::{ExtMap, ExtValue}, ExtBuilder, RefRtpPacket};
 
 #[derive(Debug, Clone, Copy)]
 pub struct AudioLevelValue {
@@ -16,6 +16,8 @@ impl Default for AudioLevelValue {
 }
 
 impl AudioLevelValue {
+    pub const URI: &'static str = "urn:ietf:params:rtp-hdrext:ssrc-audio-level";
+
     pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
         if data.len() < 1 {
             return Err(RtpError::NotEnoughBuffer {
@@ -45,6 +47,14 @@ impl AudioLevelValue {
     }
 }
 
+impl<'a> ExtValue<'a> for AudioLevelValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord)]
 pub struct AudioLevelVolume(pub u8);
 
@@ -96,10 +106,92 @@ impl std::ops::Sub<u8> for AudioLevelVolume {
 
 
 
+
+/// Mixer-to-client levels of RFC 6465, in the same order as CSRC list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsrcAudioLevels<'a>(pub &'a [u8]);
+
+impl<'a> CsrcAudioLevels<'a> {
+    pub const URI: &'static str = "urn:ietf:params:rtp-hdrext:csrc-audio-level";
+
+    pub const MAX_COUNT: usize = 15;
+
+    pub fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        if data.len() > Self::MAX_COUNT {
+            return Err(RtpError::InvalidExtLength(data.len()));
+        }
+        Ok(Self(data))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = AudioLevelVolume> + 'a {
+        self.0.iter().map(|v| AudioLevelVolume(v & AudioLevelVolume::MASK))
+    }
+
+    /// Write levels as element id, which should be aligned with CSRCs of packet
+    pub fn build(builder: &mut ExtBuilder<'_>, id: u8, levels: &[AudioLevelVolume]) -> Result<(), RtpError> {
+        if levels.len() > Self::MAX_COUNT {
+            return Err(RtpError::InvalidExtLength(levels.len()));
+        }
+        builder.format().check_len(levels.len())?;
+
+        let mut item = builder.ext(id);
+        for level in levels {
+            item.write_u8(level.0 & AudioLevelVolume::MASK);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ExtValue<'a> for CsrcAudioLevels<'a> {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+impl<'a> RefRtpPacket<'a> {
+    /// Pair CSRCs with levels of RFC 6465 extension, None if the extension is absent
+    pub fn csrc_level_iter(
+        &self,
+        map: &ExtMap,
+    ) -> Result<Option<impl Iterator<Item = (u32, AudioLevelVolume)> + 'a>, RtpError> {
+        let Some(levels) = self.ext_value::<CsrcAudioLevels>(map)? else {
+            return Ok(None)
+        };
+        Ok(Some(self.csrc_iter().zip(levels.iter())))
+    }
+}
+
+
 #[cfg(test)]
 mod test {
+    use crate::rtp::{extension::ExtFormat, extmap::ExtMap, RefRtpPacket, RtpBuilder, Seq, Timestamp};
+
     use super::*;
 
+    #[test]
+    fn test_csrc_levels() {
+        let mut map = ExtMap::new();
+        map.insert(3, CsrcAudioLevels::URI).unwrap();
+
+        let csrcs = [1111_u32, 2222, 3333];
+        let levels = [AudioLevelVolume(10), AudioLevelVolume::MAX, AudioLevelVolume::MIN];
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtpBuilder::from_basic(&mut buf, false, 111, Seq(1), Timestamp(960), 9999, csrcs.into_iter())
+            .extension(ExtFormat::OneByte);
+        CsrcAudioLevels::build(&mut builder, 3, &levels).unwrap();
+        let len = builder.payload(&[1, 2, 3], false);
+
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+        let pairs: Vec<_> = packet.csrc_level_iter(&map).unwrap().unwrap().collect();
+        assert_eq!(pairs, [(1111, levels[0]), (2222, levels[1]), (3333, levels[2])]);
+
+        assert!(packet.csrc_level_iter(&ExtMap::new()).unwrap().is_none());
+        assert!(CsrcAudioLevels::parse(&[0; 16]).is_err());
+    }
+
     #[test]
     fn test() {
         assert!(AudioLevelVolume(0) == AudioLevelVolume(0));
@@ -0,0 +1,607 @@
+//! https://datatracker.ietf.org/doc/html/rfc6184
+//!
+
+use std::collections::VecDeque;
+
+use crate::rtp::{error::RtpError, RefRtpPacket, Seq, Timestamp};
+
+use super::{nalu::{FuAssembler, NaluFormat}, writer::{sealed::WriteFrame, ExtFn, PacketWriter}};
+
+
+pub const NAL_SLICE: u8 = 1;
+pub const NAL_IDR: u8 = 5;
+pub const NAL_SEI: u8 = 6;
+pub const NAL_SPS: u8 = 7;
+pub const NAL_PPS: u8 = 8;
+pub const NAL_AUD: u8 = 9;
+pub const NAL_STAP_A: u8 = 24;
+pub const NAL_STAP_B: u8 = 25;
+pub const NAL_MTAP16: u8 = 26;
+pub const NAL_MTAP24: u8 = 27;
+pub const NAL_FU_A: u8 = 28;
+pub const NAL_FU_B: u8 = 29;
+
+#[inline]
+pub fn nal_type(header: u8) -> u8 {
+    header & 0x1F
+}
+
+
+/*
+    FU-A
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   | FU indicator  |   FU header   |                               |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               |
+   |                         FU payload                            |
+
+    FU indicator: F(1) NRI(2) Type=28(5)
+    FU header:    S(1) E(1) R(1) Type(5)
+
+    STAP-A
+
+   | STAP-A NAL HDR |  NALU 1 Size (16)  | NALU 1 HDR | NALU 1 Data | NALU 2 Size ...
+*/
+
+const FU_START: u8 = 0b1000_0000;
+const FU_END: u8 = 0b0100_0000;
+
+
+/// Access unit assembled from RTP packets of the same timestamp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct H264AccessUnit {
+    pub timestamp: Timestamp,
+    /// NAL units in the output format of depacketizer
+    pub data: Vec<u8>,
+    pub has_idr: bool,
+    pub has_sps: bool,
+    pub has_pps: bool,
+    /// false if packet loss was detected or marker bit was not seen
+    pub complete: bool,
+}
+
+impl H264AccessUnit {
+    /// IDR slice present, parameter sets may come out of band
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.has_idr
+    }
+}
+
+/// Assemble access units on marker bit or timestamp change
+///
+/// Push packets in sequence order, then pop the finished access units.
+pub struct H264Depacketizer {
+    format: NaluFormat,
+    frames: VecDeque<H264AccessUnit>,
+    current: Option<H264AccessUnit>,
+    /// NAL unit being reassembled from FU-A
+    fu: FuAssembler,
+    last_seq: Option<Seq>,
+}
+
+impl H264Depacketizer {
+
+    pub fn new(format: NaluFormat) -> Self {
+        Self {
+            format,
+            frames: VecDeque::new(),
+            current: None,
+            fu: FuAssembler::new(format),
+            last_seq: None,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<H264AccessUnit> {
+        self.frames.pop_front()
+    }
+
+    /// Unsupported packetization types are reported as error and mark the access unit incomplete.
+    /// Packets not newer than the last pushed one, e.g. retransmitted duplicates, are ignored.
+    pub fn push(&mut self, packet: &RefRtpPacket<'_>) -> Result<(), RtpError> {
+        let header = packet.header();
+        let timestamp = header.timestamp();
+        let seq = header.seq();
+
+        if matches!(self.last_seq, Some(last) if seq <= last) {
+            return Ok(());
+        }
+
+        let lost = matches!(self.last_seq, Some(last) if !last.precedes(seq));
+        self.last_seq = Some(seq);
+
+        if matches!(&self.current, Some(au) if au.timestamp != timestamp) {
+            self.flush(false);
+        }
+
+        let au = self.current.get_or_insert_with(|| H264AccessUnit {
+            timestamp,
+            data: Vec::new(),
+            has_idr: false,
+            has_sps: false,
+            has_pps: false,
+            complete: true,
+        });
+
+        if lost {
+            au.complete = false;
+            self.fu.abandon(&mut au.data);
+        }
+
+        let r = self.push_payload(packet.payload());
+        if r.is_err() {
+            if let Some(au) = self.current.as_mut() {
+                au.complete = false;
+            }
+        }
+
+        if header.mark_flag() {
+            self.flush(true);
+        }
+        r
+    }
+
+    fn push_payload(&mut self, payload: &[u8]) -> Result<(), RtpError> {
+        if payload.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 1,
+                actual: 0,
+                origin: "H264 payload",
+            });
+        }
+
+        match nal_type(payload[0]) {
+            1..=23 => {
+                self.abandon_fu();
+                self.write_nalu(payload);
+                Ok(())
+            },
+            NAL_STAP_A => {
+                self.abandon_fu();
+                self.push_stap_a(&payload[1..])
+            },
+            NAL_FU_A => self.push_fu_a(payload),
+            t => Err(RtpError::UnsupportedNalType(t)),
+        }
+    }
+
+    fn push_stap_a(&mut self, mut buf: &[u8]) -> Result<(), RtpError> {
+        while !buf.is_empty() {
+            if buf.len() < 2 {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: 2,
+                    actual: buf.len(),
+                    origin: "H264 STAP-A unit size",
+                });
+            }
+
+            let size = u16::from_be_bytes([buf[0], buf[1]]) as usize;
+            if size == 0 || buf.len() < 2 + size {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: 2 + size,
+                    actual: buf.len(),
+                    origin: "H264 STAP-A unit",
+                });
+            }
+            self.write_nalu(&buf[2..2 + size]);
+            buf = &buf[2 + size..];
+        }
+        Ok(())
+    }
+
+    fn push_fu_a(&mut self, payload: &[u8]) -> Result<(), RtpError> {
+        if payload.len() < 2 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 2,
+                actual: payload.len(),
+                origin: "H264 FU-A header",
+            });
+        }
+
+        let indicator = payload[0];
+        let fu_header = payload[1];
+        let au = self.current.as_mut().expect("H264 access unit");
+
+        if fu_header & FU_START != 0 {
+            if self.fu.abandon(&mut au.data) {
+                // end fragment lost
+                au.complete = false;
+            }
+            let nal_header = (indicator & 0xE0) | nal_type(fu_header);
+            self.fu.start(&mut au.data, &[nal_header]);
+        } else if !self.fu.is_active() {
+            // start fragment lost
+            au.complete = false;
+            return Ok(());
+        }
+
+        self.fu.append(&mut au.data, &payload[2..]);
+
+        if fu_header & FU_END != 0 {
+            self.fu.end(&mut au.data);
+            mark_nal_type(au, nal_type(fu_header));
+        }
+        Ok(())
+    }
+
+    /// Drop the unfinished FU-A when another unit arrives
+    fn abandon_fu(&mut self) {
+        let au = self.current.as_mut().expect("H264 access unit");
+        if self.fu.abandon(&mut au.data) {
+            au.complete = false;
+        }
+    }
+
+    fn write_nalu(&mut self, nalu: &[u8]) {
+        let au = self.current.as_mut().expect("H264 access unit");
+        self.format.write(&mut au.data, nalu);
+        mark_nal_type(au, nal_type(nalu[0]));
+    }
+
+    fn flush(&mut self, marker: bool) {
+        let Some(mut au) = self.current.take() else {
+            return
+        };
+
+        if self.fu.abandon(&mut au.data) {
+            // unfinished FU-A
+            au.complete = false;
+        }
+
+        au.complete &= marker;
+
+        if !au.data.is_empty() {
+            self.frames.push_back(au);
+        }
+    }
+}
+
+fn mark_nal_type(au: &mut H264AccessUnit, t: u8) {
+    match t {
+        NAL_IDR => au.has_idr = true,
+        NAL_SPS => au.has_sps = true,
+        NAL_PPS => au.has_pps = true,
+        _ => {},
+    }
+}
+
+
+/// packetization-mode of SDP fmtp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketizationMode {
+    /// One NAL unit per packet, no aggregation nor fragmentation
+    SingleNal = 0,
+    /// STAP-A and FU-A allowed
+    NonInterleaved = 1,
+}
+
+/// Split access units into RTP packets no larger than MTU
+///
+/// Small NAL units are aggregated into STAP-A, large ones are fragmented into FU-A.
+/// The marker bit is set on the last packet of each access unit.
+pub struct H264Packetizer {
+    format: NaluFormat,
+    mode: PacketizationMode,
+    writer: PacketWriter,
+}
+
+impl H264Packetizer {
+    pub const DEFAULT_MTU: usize = PacketWriter::DEFAULT_MTU;
+
+    pub fn new(format: NaluFormat, payload_type: u8, ssrc: u32, seq: Seq) -> Self {
+        Self {
+            format,
+            mode: PacketizationMode::NonInterleaved,
+            writer: PacketWriter::new(payload_type, ssrc, seq),
+        }
+    }
+
+    pub fn mode(&mut self, mode: PacketizationMode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    fn flush_stap<E>(&mut self, stap: &mut Vec<&[u8]>, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        match stap.len() {
+            0 => {},
+            1 => {
+                self.writer.next_payload(timestamp, ext_fn)?.extend_from_slice(stap[0]);
+            },
+            _ => {
+                // F is the OR and NRI the max of aggregated units
+                let f = stap.iter().fold(0, |f, nalu| f | (nalu[0] & 0x80));
+                let nri = stap.iter().map(|nalu| nalu[0] & 0x60).max().unwrap_or(0);
+
+                let payload = self.writer.next_payload(timestamp, ext_fn)?;
+                payload.push(f | nri | NAL_STAP_A);
+                for nalu in stap.iter() {
+                    payload.extend_from_slice(&(nalu.len() as u16).to_be_bytes());
+                    payload.extend_from_slice(nalu);
+                }
+            },
+        }
+        stap.clear();
+        Ok(())
+    }
+
+    fn write_fu_a<E>(&mut self, nalu: &[u8], max_payload: usize, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let indicator = (nalu[0] & 0xE0) | NAL_FU_A;
+        let t = nal_type(nalu[0]);
+
+        let mut chunks = nalu[1..].chunks(max_payload - 2).peekable();
+        let mut start = FU_START;
+
+        while let Some(chunk) = chunks.next() {
+            let end = if chunks.peek().is_none() { FU_END } else { 0 };
+
+            let payload = self.writer.next_payload(timestamp, ext_fn)?;
+            payload.push(indicator);
+            payload.push(start | end | t);
+            payload.extend_from_slice(chunk);
+
+            start = 0;
+        }
+        Ok(())
+    }
+}
+
+impl WriteFrame for H264Packetizer {
+    #[inline]
+    fn writer(&self) -> &PacketWriter {
+        &self.writer
+    }
+
+    #[inline]
+    fn writer_mut(&mut self) -> &mut PacketWriter {
+        &mut self.writer
+    }
+
+    fn write_frame<E>(&mut self, timestamp: Timestamp, au: &[u8], ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let max_payload = self.writer.max_payload(3, "H264 mtu")?;
+
+        let mut stap: Vec<&[u8]> = Vec::new();
+        let mut stap_len = 1;
+
+        for nalu in self.format.iter(au) {
+            if self.mode == PacketizationMode::SingleNal {
+                if nalu.len() > max_payload {
+                    return Err(RtpError::NotEnoughBuffer {
+                        expect: nalu.len(),
+                        actual: max_payload,
+                        origin: "H264 single NAL unit",
+                    });
+                }
+                self.writer.next_payload(timestamp, ext_fn)?.extend_from_slice(nalu);
+                continue;
+            }
+
+            if nalu.len() + 2 + stap_len <= max_payload {
+                stap.push(nalu);
+                stap_len += 2 + nalu.len();
+                continue;
+            }
+
+            self.flush_stap(&mut stap, timestamp, ext_fn)?;
+            stap_len = 1;
+
+            if nalu.len() + 3 <= max_payload {
+                stap.push(nalu);
+                stap_len += 2 + nalu.len();
+            } else if nalu.len() <= max_payload {
+                self.writer.next_payload(timestamp, ext_fn)?.extend_from_slice(nalu);
+            } else {
+                self.write_fu_a(nalu, max_payload, timestamp, ext_fn)?;
+            }
+        }
+
+        self.flush_stap(&mut stap, timestamp, ext_fn)?;
+        self.writer.finish(timestamp, ext_fn)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{codecs::{nalu::NaluFormat, test_util::{payload_250, rtp_packet, MTU}, Packetizer}, error::RtpError, extension::ExtFormat, RefRtpPacket, Seq, Timestamp};
+
+    use super::{H264Depacketizer, H264Packetizer, PacketizationMode};
+
+    fn push(d: &mut H264Depacketizer, seq: u16, ts: u32, mark: bool, payload: &[u8]) -> Result<(), RtpError> {
+        d.push(&RefRtpPacket::parse(&rtp_packet(seq, ts, mark, payload)).unwrap())
+    }
+
+    #[test]
+    fn test_depacketize() {
+        let mut d = H264Depacketizer::new(NaluFormat::AnnexB);
+
+        // STAP-A of SPS and PPS, then IDR in FU-A
+        push(&mut d, 1, 3000, false, &[0x78, 0, 2, 0x67, 0xAA, 0, 2, 0x68, 0xBB]).unwrap();
+        push(&mut d, 2, 3000, false, &[0x7C, 0x85, 1, 2]).unwrap();
+        push(&mut d, 3, 3000, false, &[0x7C, 0x05, 3]).unwrap();
+        push(&mut d, 4, 3000, true, &[0x7C, 0x45, 4]).unwrap();
+
+        let au = d.pop().unwrap();
+        assert!(au.complete);
+        assert!(au.is_keyframe() && au.has_sps && au.has_pps);
+        assert_eq!(au.data, [
+            0, 0, 0, 1, 0x67, 0xAA,
+            0, 0, 0, 1, 0x68, 0xBB,
+            0, 0, 0, 1, 0x65, 1, 2, 3, 4,
+        ]);
+        assert!(d.pop().is_none());
+
+        // lost middle fragment of a P frame, the partial unit is removed
+        push(&mut d, 5, 6000, false, &[0x06, 0xEE]).unwrap();
+        push(&mut d, 6, 6000, false, &[0x5C, 0x81, 1]).unwrap();
+        push(&mut d, 8, 6000, true, &[0x5C, 0x41, 3]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert!(!au.is_keyframe());
+        assert_eq!(au.data, [0, 0, 0, 1, 0x06, 0xEE]);
+
+        // timestamp change without marker flushes
+        push(&mut d, 9, 9000, false, &[0x41, 1]).unwrap();
+        push(&mut d, 10, 12000, true, &[0x41, 2]).unwrap();
+        assert!(!d.pop().unwrap().complete);
+        assert_eq!(d.pop().unwrap().data, [0, 0, 0, 1, 0x41, 2]);
+
+        // AVCC and unsupported types
+        let mut d = H264Depacketizer::new(NaluFormat::Avcc);
+        assert!(matches!(push(&mut d, 1, 0, false, &[0x59, 0]), Err(RtpError::UnsupportedNalType(25))));
+        push(&mut d, 2, 0, false, &[0x7C, 0x85, 1, 2]).unwrap();
+        push(&mut d, 3, 0, true, &[0x7C, 0x45, 3]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert_eq!(au.data, [0, 0, 0, 4, 0x65, 1, 2, 3]);
+
+        // truncated size of STAP-A
+        assert!(push(&mut d, 4, 3000, true, &[0x78, 0, 2, 0x67, 0xAA, 0]).is_err());
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert_eq!(au.data, [0, 0, 0, 2, 0x67, 0xAA]);
+    }
+
+    #[test]
+    fn test_avcc_fu_loss() {
+        let mut d = H264Depacketizer::new(NaluFormat::Avcc);
+
+        // lost end fragment before a new FU-A
+        push(&mut d, 1, 3000, false, &[0x06, 0xEE]).unwrap();
+        push(&mut d, 2, 3000, false, &[0x7C, 0x85, 1, 2]).unwrap();
+        push(&mut d, 3, 3000, false, &[0x5C, 0x81, 3]).unwrap();
+        push(&mut d, 4, 3000, true, &[0x5C, 0x41, 4]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete && !au.is_keyframe());
+        assert_eq!(au.data, [0, 0, 0, 2, 0x06, 0xEE, 0, 0, 0, 3, 0x41, 3, 4]);
+
+        // sequence gap in the middle
+        push(&mut d, 5, 6000, false, &[0x06, 0xEE]).unwrap();
+        push(&mut d, 6, 6000, false, &[0x5C, 0x81, 1]).unwrap();
+        push(&mut d, 8, 6000, false, &[0x5C, 0x01, 2]).unwrap();
+        push(&mut d, 9, 6000, true, &[0x41, 5]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert_eq!(au.data, [0, 0, 0, 2, 0x06, 0xEE, 0, 0, 0, 2, 0x41, 5]);
+
+        // timestamp change in the middle
+        push(&mut d, 10, 9000, false, &[0x06, 0xEE]).unwrap();
+        push(&mut d, 11, 9000, false, &[0x5C, 0x81, 1]).unwrap();
+        push(&mut d, 12, 12000, true, &[0x41, 6]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert_eq!(au.data, [0, 0, 0, 2, 0x06, 0xEE]);
+        assert_eq!(d.pop().unwrap().data, [0, 0, 0, 2, 0x41, 6]);
+
+        assert!(NaluFormat::Avcc.iter(&au.data).eq([&[0x06, 0xEE][..]]));
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let mut d = H264Depacketizer::new(NaluFormat::AnnexB);
+
+        // duplicated FU-A fragment and a late older packet are dropped
+        push(&mut d, 0xFFFF, 3000, false, &[0x7C, 0x85, 1, 2]).unwrap();
+        push(&mut d, 0, 3000, false, &[0x7C, 0x05, 3]).unwrap();
+        push(&mut d, 0, 3000, false, &[0x7C, 0x05, 3]).unwrap();
+        push(&mut d, 0xFFFF, 3000, false, &[0x7C, 0x85, 1, 2]).unwrap();
+        push(&mut d, 1, 3000, true, &[0x7C, 0x45, 4]).unwrap();
+
+        let au = d.pop().unwrap();
+        assert!(au.complete && au.is_keyframe());
+        assert_eq!(au.data, [0, 0, 0, 1, 0x65, 1, 2, 3, 4]);
+
+        // retransmitted marker packet doesn't produce another unit
+        push(&mut d, 1, 3000, true, &[0x7C, 0x45, 4]).unwrap();
+        assert!(d.pop().is_none());
+    }
+
+    #[test]
+    fn test_packetize() {
+        let mut au = vec![0, 0, 0, 1, 0x67, 0xAA, 0, 0, 0, 1, 0x68, 0xBB, 0, 0, 0, 1, 0x65];
+        au.extend(payload_250());
+
+        let mut packetizer = H264Packetizer::new(NaluFormat::AnnexB, 96, 1111, Seq(100));
+        packetizer.mtu(MTU).ext_budget(8);
+
+        let mut packets = Vec::new();
+        packetizer.packetize_with_ext(
+            Timestamp(3000),
+            &au,
+            |builder, _mark| Ok(builder.extension_one(1, &[0x01, 0x02])),
+            |packet| packets.push(packet.to_vec()),
+        ).unwrap();
+
+        // STAP-A, then 250 bytes in FU-A of 78 bytes at most
+        assert_eq!(packets.len(), 5);
+        assert!(packets.iter().all(|x| x.len() <= MTU));
+        assert_eq!(packetizer.next_seq(), Seq(105));
+
+        let mut d = H264Depacketizer::new(NaluFormat::AnnexB);
+        for (i, packet) in packets.iter().enumerate() {
+            let packet = RefRtpPacket::parse(packet).unwrap();
+            assert_eq!(packet.header().mark_flag(), i == 4);
+            assert_eq!(packet.ext_by_id(1), Some(&[0x01, 0x02][..]));
+            d.push(&packet).unwrap();
+        }
+        let out = d.pop().unwrap();
+        assert!(out.complete && out.is_keyframe());
+        assert_eq!(out.data, au);
+
+        // extensions beyond budget on the last packet, earlier packets are not emitted
+        let mut packets = Vec::new();
+        assert!(matches!(
+            packetizer.packetize_with_ext(
+                Timestamp(6000),
+                &au,
+                |builder, mark| match mark {
+                    true => Ok(builder.extension_one(1, &[0; 16])),
+                    false => Ok(builder.extension_one(1, &[0x01, 0x02])),
+                },
+                |packet| packets.push(packet.to_vec()),
+            ),
+            Err(RtpError::NotEnoughBuffer { expect: 36, actual: 20, .. })
+        ));
+        assert!(packets.is_empty());
+        assert_eq!(packetizer.next_seq(), Seq(105));
+
+        // extensions far beyond MTU
+        assert!(matches!(
+            packetizer.packetize_with_ext(
+                Timestamp(6000),
+                &au,
+                |builder, _mark| {
+                    let mut builder = builder.extension(ExtFormat::TwoByte);
+                    for id in 1..=10 {
+                        builder.try_write_ext(id, &[0; 255])?;
+                    }
+                    Ok(builder.payload_builder())
+                },
+                |packet| packets.push(packet.to_vec()),
+            ),
+            Err(RtpError::NotEnoughBuffer { .. })
+        ));
+        assert!(packets.is_empty());
+        assert_eq!(packetizer.next_seq(), Seq(105));
+
+        // single NAL mode, SPS and PPS are not emitted before the oversized IDR
+        packetizer.mode(PacketizationMode::SingleNal);
+        assert!(packetizer.packetize(Timestamp(9000), &au, |packet| packets.push(packet.to_vec())).is_err());
+        assert!(packets.is_empty());
+        assert_eq!(packetizer.next_seq(), Seq(105));
+
+        packetizer.packetize(Timestamp(9000), &au[..12], |packet| packets.push(packet.to_vec())).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(RefRtpPacket::parse(&packets[1]).unwrap().payload(), &[0x68, 0xBB]);
+    }
+}
@@ -0,0 +1,699 @@
+//! https://datatracker.ietf.org/doc/html/rfc7798
+//!
+
+use std::collections::VecDeque;
+
+use crate::rtp::{error::RtpError, RefRtpPacket, Seq, Timestamp};
+
+use super::{nalu::{FuAssembler, NaluFormat}, writer::{sealed::WriteFrame, ExtFn, PacketWriter}};
+
+
+pub const NAL_BLA_W_LP: u8 = 16;
+pub const NAL_IDR_W_RADL: u8 = 19;
+pub const NAL_IDR_N_LP: u8 = 20;
+pub const NAL_CRA: u8 = 21;
+pub const NAL_RSV_IRAP_23: u8 = 23;
+pub const NAL_VPS: u8 = 32;
+pub const NAL_SPS: u8 = 33;
+pub const NAL_PPS: u8 = 34;
+pub const NAL_AUD: u8 = 35;
+pub const NAL_PREFIX_SEI: u8 = 39;
+pub const NAL_AP: u8 = 48;
+pub const NAL_FU: u8 = 49;
+pub const NAL_PACI: u8 = 50;
+
+/// NAL unit type from the first byte of header
+#[inline]
+pub fn nal_type(header: u8) -> u8 {
+    (header >> 1) & 0x3F
+}
+
+/// Intra random access point, BLA, IDR or CRA
+#[inline]
+pub fn is_irap(t: u8) -> bool {
+    (NAL_BLA_W_LP..=NAL_RSV_IRAP_23).contains(&t)
+}
+
+
+/*
+    NAL unit header and PayloadHdr
+
+   +---------------+---------------+
+   |0|1|2|3|4|5|6|7|0|1|2|3|4|5|6|7|
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |F|   Type    |  LayerId  | TID |
+   +-------------+-----------------+
+
+    AP:   PayloadHdr(Type=48) [DONL(16)] NALU 1 Size(16) NALU 1 | [DOND(8)] NALU 2 Size(16) NALU 2 ...
+
+    FU:   PayloadHdr(Type=49) FU header [DONL(16)] FU payload
+          FU header: S(1) E(1) FuType(6), DONL only in the first fragment
+
+    PACI: PayloadHdr(Type=50) A(1) cType(6) PHSsize(5) F0 F1 F2 Y(4) PHES PACI payload
+          PACI payload is a single NAL unit, AP or FU whose PayloadHdr type is cType
+*/
+
+const FU_START: u8 = 0b1000_0000;
+const FU_END: u8 = 0b0100_0000;
+
+const DONL_LEN: usize = 2;
+const DOND_LEN: usize = 1;
+
+#[inline]
+fn with_type(header: [u8; 2], t: u8) -> [u8; 2] {
+    [(header[0] & 0x81) | (t << 1), header[1]]
+}
+
+
+/// Access unit assembled from RTP packets of the same timestamp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct H265AccessUnit {
+    pub timestamp: Timestamp,
+    /// NAL units in the output format of depacketizer
+    pub data: Vec<u8>,
+    /// decoding order number of each NAL unit in data, empty without DONL
+    pub dons: Vec<u16>,
+    pub has_irap: bool,
+    pub has_vps: bool,
+    pub has_sps: bool,
+    pub has_pps: bool,
+    /// false if packet loss was detected or marker bit was not seen
+    pub complete: bool,
+}
+
+impl H265AccessUnit {
+    /// IRAP picture present, parameter sets may come out of band
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.has_irap
+    }
+}
+
+/// Assemble access units on marker bit or timestamp change
+///
+/// Push packets in sequence order, then pop the finished access units.
+/// With DONL, NAL units of an access unit are output in decoding order,
+/// DON from DONL of AP, single NAL unit and FU, plus DOND + 1 for the following units of AP.
+pub struct H265Depacketizer {
+    format: NaluFormat,
+    donl: bool,
+    frames: VecDeque<H265AccessUnit>,
+    current: Option<H265AccessUnit>,
+    /// NAL unit being reassembled from FU
+    fu: FuAssembler,
+    /// DON and offset of each NAL unit in the current access unit
+    units: Vec<(u16, usize)>,
+    last_seq: Option<Seq>,
+}
+
+impl H265Depacketizer {
+
+    pub fn new(format: NaluFormat) -> Self {
+        Self {
+            format,
+            donl: false,
+            frames: VecDeque::new(),
+            current: None,
+            fu: FuAssembler::new(format),
+            units: Vec::new(),
+            last_seq: None,
+        }
+    }
+
+    /// DONL fields present, when sprop-max-don-diff > 0
+    pub fn donl(&mut self, donl: bool) -> &mut Self {
+        self.donl = donl;
+        self
+    }
+
+    pub fn pop(&mut self) -> Option<H265AccessUnit> {
+        self.frames.pop_front()
+    }
+
+    /// Unsupported payload types are reported as error and mark the access unit incomplete
+    pub fn push(&mut self, packet: &RefRtpPacket<'_>) -> Result<(), RtpError> {
+        let header = packet.header();
+        let timestamp = header.timestamp();
+        let seq = header.seq();
+
+        let lost = matches!(self.last_seq, Some(last) if !last.precedes(seq));
+        self.last_seq = Some(seq);
+
+        if matches!(&self.current, Some(au) if au.timestamp != timestamp) {
+            self.flush(false);
+        }
+
+        let au = self.current.get_or_insert_with(|| H265AccessUnit {
+            timestamp,
+            data: Vec::new(),
+            dons: Vec::new(),
+            has_irap: false,
+            has_vps: false,
+            has_sps: false,
+            has_pps: false,
+            complete: true,
+        });
+
+        if lost {
+            au.complete = false;
+            self.abandon_fu();
+        }
+
+        let r = self.push_payload(packet.payload());
+        if r.is_err() {
+            if let Some(au) = self.current.as_mut() {
+                au.complete = false;
+            }
+        }
+
+        if header.mark_flag() {
+            self.flush(true);
+        }
+        r
+    }
+
+    fn push_payload(&mut self, payload: &[u8]) -> Result<(), RtpError> {
+        if payload.len() < 2 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 2,
+                actual: payload.len(),
+                origin: "H265 payload header",
+            });
+        }
+
+        self.push_unit([payload[0], payload[1]], &payload[2..])
+    }
+
+    fn push_unit(&mut self, header: [u8; 2], body: &[u8]) -> Result<(), RtpError> {
+        match nal_type(header[0]) {
+            0..=47 => {
+                self.abandon_fu();
+                let (don, body) = self.read_don(body, DONL_LEN, "H265 DONL")?;
+                self.write_nalu(don, header, body);
+                Ok(())
+            },
+            NAL_AP => {
+                self.abandon_fu();
+                self.push_ap(body)
+            },
+            NAL_FU => self.push_fu(header, body),
+            NAL_PACI => self.push_paci(header, body),
+            t => Err(RtpError::UnsupportedNalType(t)),
+        }
+    }
+
+    fn push_ap(&mut self, body: &[u8]) -> Result<(), RtpError> {
+        let (mut don, mut buf) = self.read_don(body, DONL_LEN, "H265 AP DONL")?;
+        let mut first = true;
+
+        while !buf.is_empty() {
+            if !first {
+                let (dond, rest) = self.read_don(buf, DOND_LEN, "H265 AP DOND")?;
+                don = don.wrapping_add(dond).wrapping_add(1);
+                buf = rest;
+            }
+            first = false;
+
+            if buf.len() < 2 {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: 2,
+                    actual: buf.len(),
+                    origin: "H265 AP unit size",
+                });
+            }
+
+            let size = u16::from_be_bytes([buf[0], buf[1]]) as usize;
+            if size < 2 || buf.len() < 2 + size {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: 2 + size.max(2),
+                    actual: buf.len(),
+                    origin: "H265 AP unit",
+                });
+            }
+
+            let nalu = &buf[2..2 + size];
+            self.write_nalu(don, [nalu[0], nalu[1]], &nalu[2..]);
+            buf = &buf[2 + size..];
+        }
+        Ok(())
+    }
+
+    fn push_fu(&mut self, header: [u8; 2], body: &[u8]) -> Result<(), RtpError> {
+        if body.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 1,
+                actual: 0,
+                origin: "H265 FU header",
+            });
+        }
+
+        let fu_header = body[0];
+        let mut body = &body[1..];
+        let fu_type = fu_header & 0x3F;
+
+        if fu_header & FU_START != 0 {
+            let (don, rest) = self.read_don(body, DONL_LEN, "H265 FU DONL")?;
+            body = rest;
+
+            self.abandon_fu();
+            let au = self.current.as_mut().expect("H265 access unit");
+            self.units.push((don, au.data.len()));
+            self.fu.start(&mut au.data, &with_type(header, fu_type));
+        } else if !self.fu.is_active() {
+            // start fragment lost
+            let au = self.current.as_mut().expect("H265 access unit");
+            au.complete = false;
+            return Ok(());
+        }
+
+        let au = self.current.as_mut().expect("H265 access unit");
+        self.fu.append(&mut au.data, body);
+
+        if fu_header & FU_END != 0 {
+            self.fu.end(&mut au.data);
+            mark_nal_type(au, fu_type);
+        }
+        Ok(())
+    }
+
+    fn push_paci(&mut self, header: [u8; 2], body: &[u8]) -> Result<(), RtpError> {
+        if body.len() < 2 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 2,
+                actual: body.len(),
+                origin: "H265 PACI header",
+            });
+        }
+
+        let c_type = (body[0] >> 1) & 0x3F;
+        let phs_size = (((body[0] & 0x01) << 4) | (body[1] >> 4)) as usize;
+        if body.len() < 2 + phs_size {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 2 + phs_size,
+                actual: body.len(),
+                origin: "H265 PACI PHES",
+            });
+        }
+
+        if c_type == NAL_PACI {
+            return Err(RtpError::UnsupportedNalType(c_type));
+        }
+
+        // PHES carries no data for decoding
+        self.push_unit(with_type(header, c_type), &body[2 + phs_size..])
+    }
+
+    /// Read DONL or DOND of len bytes if present, 0 otherwise
+    fn read_don<'b>(&self, buf: &'b [u8], len: usize, origin: &'static str) -> Result<(u16, &'b [u8]), RtpError> {
+        if !self.donl {
+            return Ok((0, buf));
+        }
+
+        if buf.len() < len {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: len,
+                actual: buf.len(),
+                origin,
+            });
+        }
+
+        let value = buf[..len].iter().fold(0_u16, |v, x| (v << 8) | *x as u16);
+        Ok((value, &buf[len..]))
+    }
+
+    fn write_nalu(&mut self, don: u16, header: [u8; 2], body: &[u8]) {
+        let au = self.current.as_mut().expect("H265 access unit");
+        self.units.push((don, au.data.len()));
+        let offset = self.format.begin(&mut au.data);
+        au.data.extend_from_slice(&header);
+        au.data.extend_from_slice(body);
+        self.format.end(&mut au.data, offset);
+        mark_nal_type(au, nal_type(header[0]));
+    }
+
+    /// Remove the unfinished FU from the current access unit
+    fn abandon_fu(&mut self) {
+        if let Some(au) = self.current.as_mut() {
+            if self.fu.abandon(&mut au.data) {
+                au.complete = false;
+                self.units.pop();
+            }
+        }
+    }
+
+    fn flush(&mut self, marker: bool) {
+        self.abandon_fu();
+        let units = std::mem::take(&mut self.units);
+
+        let Some(mut au) = self.current.take() else {
+            return
+        };
+
+        au.complete &= marker;
+
+        if self.donl {
+            reorder(&mut au, &units);
+        }
+
+        if !au.data.is_empty() {
+            self.frames.push_back(au);
+        }
+    }
+}
+
+/// Sort NAL units by DON, which wraps around and is compared to the first unit
+fn reorder(au: &mut H265AccessUnit, units: &[(u16, usize)]) {
+    let Some(&(base, _)) = units.first() else {
+        return
+    };
+
+    let mut ranges: Vec<_> = units.iter()
+        .enumerate()
+        .map(|(i, (don, start))| {
+            let end = units.get(i + 1).map(|x| x.1).unwrap_or(au.data.len());
+            (*don, *start..end)
+        })
+        .collect();
+    ranges.sort_by_key(|(don, _)| don.wrapping_sub(base) as i16);
+
+    au.dons = ranges.iter().map(|(don, _)| *don).collect();
+
+    if ranges.windows(2).any(|w| w[0].1.start > w[1].1.start) {
+        let mut data = Vec::with_capacity(au.data.len());
+        for (_, range) in ranges {
+            data.extend_from_slice(&au.data[range]);
+        }
+        au.data = data;
+    }
+}
+
+fn mark_nal_type(au: &mut H265AccessUnit, t: u8) {
+    match t {
+        t if is_irap(t) => au.has_irap = true,
+        NAL_VPS => au.has_vps = true,
+        NAL_SPS => au.has_sps = true,
+        NAL_PPS => au.has_pps = true,
+        _ => {},
+    }
+}
+
+
+/// Split access units into RTP packets no larger than MTU
+///
+/// Small NAL units are aggregated into AP, large ones are fragmented into FU.
+/// The marker bit is set on the last packet of each access unit.
+pub struct H265Packetizer {
+    format: NaluFormat,
+    donl: bool,
+    /// decoding order number of the next NAL unit
+    don: u16,
+    writer: PacketWriter,
+}
+
+impl H265Packetizer {
+
+    pub fn new(format: NaluFormat, payload_type: u8, ssrc: u32, seq: Seq) -> Self {
+        Self {
+            format,
+            donl: false,
+            don: 0,
+            writer: PacketWriter::new(payload_type, ssrc, seq),
+        }
+    }
+
+    /// Write DONL fields, when sprop-max-don-diff > 0
+    pub fn donl(&mut self, donl: bool) -> &mut Self {
+        self.donl = donl;
+        self
+    }
+
+    fn flush_ap<E>(&mut self, ap: &mut Vec<&[u8]>, don: u16, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        match ap.len() {
+            0 => {},
+            1 => self.write_single(ap[0], don, timestamp, ext_fn)?,
+            _ => {
+                // F is the OR, LayerId and TID the lowest of aggregated units
+                let f = ap.iter().fold(0, |f, nalu| f | (nalu[0] & 0x80));
+                let layer_id = ap.iter()
+                    .map(|nalu| (u16::from_be_bytes([nalu[0], nalu[1]]) >> 3) & 0x3F)
+                    .min()
+                    .unwrap_or(0);
+                let tid = ap.iter().map(|nalu| nalu[1] & 0x07).min().unwrap_or(0);
+                let header = ((f as u16) << 8) | ((NAL_AP as u16) << 9) | (layer_id << 3) | tid as u16;
+
+                let donl = self.donl;
+                let payload = self.writer.next_payload(timestamp, ext_fn)?;
+                payload.extend_from_slice(&header.to_be_bytes());
+                if donl {
+                    payload.extend_from_slice(&don.to_be_bytes());
+                }
+                for (i, nalu) in ap.iter().enumerate() {
+                    if donl && i > 0 {
+                        // consecutive decoding order
+                        payload.push(0);
+                    }
+                    payload.extend_from_slice(&(nalu.len() as u16).to_be_bytes());
+                    payload.extend_from_slice(nalu);
+                }
+            },
+        }
+        ap.clear();
+        Ok(())
+    }
+
+    fn write_single<E>(&mut self, nalu: &[u8], don: u16, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let donl = self.donl;
+        let payload = self.writer.next_payload(timestamp, ext_fn)?;
+        payload.extend_from_slice(&nalu[..2]);
+        if donl {
+            payload.extend_from_slice(&don.to_be_bytes());
+        }
+        payload.extend_from_slice(&nalu[2..]);
+        Ok(())
+    }
+
+    fn write_fu<E>(&mut self, nalu: &[u8], chunk_len: usize, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let header = with_type([nalu[0], nalu[1]], NAL_FU);
+        let t = nal_type(nalu[0]);
+        let donl = self.donl;
+        let don = self.don;
+
+        let mut chunks = nalu[2..].chunks(chunk_len).peekable();
+        let mut start = FU_START;
+
+        while let Some(chunk) = chunks.next() {
+            let end = if chunks.peek().is_none() { FU_END } else { 0 };
+
+            let payload = self.writer.next_payload(timestamp, ext_fn)?;
+            payload.extend_from_slice(&header);
+            payload.push(start | end | t);
+            if donl && start != 0 {
+                payload.extend_from_slice(&don.to_be_bytes());
+            }
+            payload.extend_from_slice(chunk);
+
+            start = 0;
+        }
+        Ok(())
+    }
+
+    fn write_au<E>(&mut self, timestamp: Timestamp, au: &[u8], ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let donl_len = if self.donl { DONL_LEN } else { 0 };
+        let dond_len = if self.donl { DOND_LEN } else { 0 };
+
+        // PayloadHdr, FU header, DONL and one byte
+        let max_payload = self.writer.max_payload(4 + donl_len, "H265 mtu")?;
+
+        let mut ap: Vec<&[u8]> = Vec::new();
+        let mut ap_len = 2 + donl_len;
+        let mut ap_don = self.don;
+
+        for nalu in self.format.iter(au) {
+            if nalu.len() < 2 {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: 2,
+                    actual: nalu.len(),
+                    origin: "H265 NAL unit header",
+                });
+            }
+
+            let cost = if ap.is_empty() { 2 + nalu.len() } else { dond_len + 2 + nalu.len() };
+            if ap_len + cost <= max_payload {
+                if ap.is_empty() {
+                    ap_don = self.don;
+                }
+                ap.push(nalu);
+                ap_len += cost;
+                self.don = self.don.wrapping_add(1);
+                continue;
+            }
+
+            self.flush_ap(&mut ap, ap_don, timestamp, ext_fn)?;
+            ap_len = 2 + donl_len;
+
+            if ap_len + 2 + nalu.len() <= max_payload {
+                ap_don = self.don;
+                ap.push(nalu);
+                ap_len += 2 + nalu.len();
+            } else if nalu.len() + donl_len <= max_payload {
+                self.write_single(nalu, self.don, timestamp, ext_fn)?;
+            } else {
+                self.write_fu(nalu, max_payload - 3 - donl_len, timestamp, ext_fn)?;
+            }
+            self.don = self.don.wrapping_add(1);
+        }
+
+        self.flush_ap(&mut ap, ap_don, timestamp, ext_fn)?;
+        self.writer.finish(timestamp, ext_fn)
+    }
+}
+
+impl WriteFrame for H265Packetizer {
+    #[inline]
+    fn writer(&self) -> &PacketWriter {
+        &self.writer
+    }
+
+    #[inline]
+    fn writer_mut(&mut self) -> &mut PacketWriter {
+        &mut self.writer
+    }
+
+    fn write_frame<E>(&mut self, timestamp: Timestamp, au: &[u8], ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let don = self.don;
+        let r = self.write_au(timestamp, au, ext_fn);
+        if r.is_err() {
+            // packets are dropped, so are their decoding order numbers
+            self.don = don;
+        }
+        r
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{codecs::{nalu::NaluFormat, test_util::{payload_250, roundtrip, rtp_packet, MTU}}, error::RtpError, RefRtpPacket, Seq, Timestamp};
+
+    use super::{H265Depacketizer, H265Packetizer};
+
+    fn push(d: &mut H265Depacketizer, seq: u16, ts: u32, mark: bool, payload: &[u8]) -> Result<(), RtpError> {
+        d.push(&RefRtpPacket::parse(&rtp_packet(seq, ts, mark, payload)).unwrap())
+    }
+
+    fn access_unit() -> Vec<u8> {
+        // VPS, SPS, PPS and IDR_W_RADL
+        let mut au = vec![
+            0, 0, 0, 1, 0x40, 0x01, 0xAA,
+            0, 0, 0, 1, 0x42, 0x01, 0xBB,
+            0, 0, 0, 1, 0x44, 0x01, 0xCC,
+            0, 0, 0, 1, 0x26, 0x01,
+        ];
+        au.extend(payload_250());
+        au
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let au = access_unit();
+
+        for donl in [false, true] {
+            let mut packetizer = H265Packetizer::new(NaluFormat::AnnexB, 96, 1111, Seq(100));
+            packetizer.donl(donl);
+
+            let mut d = H265Depacketizer::new(NaluFormat::AnnexB);
+            d.donl(donl);
+            let packets = roundtrip(&mut packetizer, 3000, &au, |packet| d.push(packet));
+
+            // AP, then 250 bytes in FU
+            let fu_len = MTU - 12 - 3 - if donl { 2 } else { 0 };
+            assert_eq!(packets.len(), 1 + 250_usize.div_ceil(fu_len));
+
+            let out = d.pop().unwrap();
+            assert!(out.complete && out.is_keyframe());
+            assert!(out.has_vps && out.has_sps && out.has_pps);
+            assert_eq!(out.data, au);
+        }
+    }
+
+    #[test]
+    fn test_depacketize() {
+        let mut d = H265Depacketizer::new(NaluFormat::Avcc);
+
+        // PACI of a TRAIL_R with 2 bytes PHES
+        push(&mut d, 1, 3000, true, &[0x64, 0x01, 0x02, 0x20, 0xEE, 0xEE, 1, 2, 3]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(au.complete && !au.is_keyframe());
+        assert_eq!(au.data, [0, 0, 0, 5, 0x02, 0x01, 1, 2, 3]);
+
+        // lost start of FU
+        push(&mut d, 3, 6000, false, &[0x62, 0x01, 0x01, 4]).unwrap();
+        push(&mut d, 4, 6000, true, &[0x62, 0x01, 0x41, 5]).unwrap();
+        assert!(d.pop().is_none());
+
+        // truncated second unit of AP
+        assert!(push(&mut d, 5, 9000, true, &[0x60, 0x01, 0, 3, 0x02, 0x01, 7, 0, 5, 0x02]).is_err());
+        assert!(!d.pop().unwrap().complete);
+
+        assert!(matches!(push(&mut d, 6, 12000, true, &[0x66, 0x01, 0]), Err(RtpError::UnsupportedNalType(51))));
+    }
+
+    #[test]
+    fn test_avcc_fu_loss() {
+        let mut d = H265Depacketizer::new(NaluFormat::Avcc);
+
+        // FU end lost, the unit is removed instead of leaving an unfilled length
+        push(&mut d, 1, 3000, false, &[0x02, 0x01, 1]).unwrap();
+        push(&mut d, 2, 3000, false, &[0x62, 0x01, 0x81, 2]).unwrap();
+        push(&mut d, 4, 3000, true, &[0x62, 0x01, 0x41, 3]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(!au.complete);
+        assert_eq!(au.data, [0, 0, 0, 3, 0x02, 0x01, 1]);
+
+        // timestamp changes in the middle of FU
+        push(&mut d, 5, 6000, false, &[0x62, 0x01, 0x81, 4]).unwrap();
+        push(&mut d, 6, 9000, true, &[0x02, 0x01, 5]).unwrap();
+        let au = d.pop().unwrap();
+        assert!(au.complete);
+        assert_eq!(au.timestamp, Timestamp(9000));
+        assert_eq!(au.data, [0, 0, 0, 3, 0x02, 0x01, 5]);
+        assert!(d.pop().is_none());
+    }
+
+    #[test]
+    fn test_don_order() {
+        let mut d = H265Depacketizer::new(NaluFormat::AnnexB);
+        d.donl(true);
+
+        // AP of DON 0xFFFF and 2 (DOND 2), single NAL unit of DON 0, FU of DON 1
+        push(&mut d, 1, 3000, false, &[0x60, 0x01, 0xFF, 0xFF, 0, 3, 0x02, 0x01, 0xA, 2, 0, 3, 0x02, 0x01, 0xD]).unwrap();
+        push(&mut d, 2, 3000, false, &[0x02, 0x01, 0, 0, 0xB]).unwrap();
+        push(&mut d, 3, 3000, false, &[0x62, 0x01, 0x81, 0, 1, 0xC]).unwrap();
+        push(&mut d, 4, 3000, true, &[0x62, 0x01, 0x41, 0xC2]).unwrap();
+
+        let au = d.pop().unwrap();
+        assert!(au.complete);
+        assert_eq!(au.dons, [0xFFFF, 0, 1, 2]);
+        assert_eq!(au.data, [
+            0, 0, 0, 1, 0x02, 0x01, 0xA,
+            0, 0, 0, 1, 0x02, 0x01, 0xB,
+            0, 0, 0, 1, 0x02, 0x01, 0xC, 0xC2,
+            0, 0, 0, 1, 0x02, 0x01, 0xD,
+        ]);
+    }
+}
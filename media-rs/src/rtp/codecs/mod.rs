@@ -0,0 +1,54 @@
+pub mod nalu;
+
+pub mod h264;
+
+pub mod h265;
+
+pub mod vp8;
+
+pub mod vp9;
+
+mod writer;
+pub use writer::Packetizer;
+
+
+#[cfg(test)]
+mod test_util {
+    use crate::rtp::{error::RtpError, RefRtpPacket, RtpBuilder, Seq, Timestamp};
+
+    use super::Packetizer;
+
+    pub(super) const MTU: usize = 100;
+
+    /// RTP packet of payload type 96 and SSRC 1111
+    pub(super) fn rtp_packet(seq: u16, ts: u32, mark: bool, payload: &[u8]) -> Vec<u8> {
+        let mut buf = vec![0_u8; 1500];
+        let len = RtpBuilder::from_basic(&mut buf, mark, 96, Seq(seq), Timestamp(ts), 1111, [].into_iter())
+            .payload(payload, false);
+        buf.truncate(len);
+        buf
+    }
+
+    /// 250 bytes without zero, never forming a start code
+    pub(super) fn payload_250() -> impl Iterator<Item = u8> {
+        (0..250).map(|x| (x % 200 + 1) as u8)
+    }
+
+    /// Packetize a frame with MTU, then push each packet to a depacketizer
+    pub(super) fn roundtrip<P, D>(packetizer: &mut P, ts: u32, frame: &[u8], mut push: D) -> Vec<Vec<u8>>
+    where
+        P: Packetizer,
+        D: FnMut(&RefRtpPacket<'_>) -> Result<(), RtpError>,
+    {
+        let mut packets = Vec::new();
+        packetizer.mtu(MTU)
+            .packetize(Timestamp(ts), frame, |packet| packets.push(packet.to_vec()))
+            .unwrap();
+        assert!(packets.iter().all(|x| x.len() <= MTU));
+
+        for packet in packets.iter() {
+            push(&RefRtpPacket::parse(packet).unwrap()).unwrap();
+        }
+        packets
+    }
+}
@@ -0,0 +1,213 @@
+//! NAL unit framing shared by H.264 and H.265
+//!
+//! Annex B: NAL units separated by start code 00 00 01 or 00 00 00 01
+//! AVCC: each NAL unit prefixed by 4 bytes big endian length
+//!
+
+use bytes::BufMut;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NaluFormat {
+    AnnexB,
+    Avcc,
+}
+
+impl NaluFormat {
+
+    pub const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+    pub const AVCC_LEN_SIZE: usize = 4;
+
+    /// Split buf into NAL units, empty units are skipped
+    pub fn iter<'a>(&self, buf: &'a [u8]) -> NaluIter<'a> {
+        NaluIter {
+            buf,
+            format: *self,
+        }
+    }
+
+    /// Append one NAL unit to out
+    pub fn write(&self, out: &mut Vec<u8>, nalu: &[u8]) {
+        match self {
+            Self::AnnexB => out.put_slice(&Self::START_CODE),
+            Self::Avcc => out.put_u32(nalu.len() as u32),
+        }
+        out.put_slice(nalu);
+    }
+
+    /// Length of start code or length prefix written before each NAL unit
+    #[inline]
+    pub fn prefix_len(&self) -> usize {
+        match self {
+            Self::AnnexB => Self::START_CODE.len(),
+            Self::Avcc => Self::AVCC_LEN_SIZE,
+        }
+    }
+
+    /// Begin a NAL unit whose body is appended later, return its header offset
+    pub(super) fn begin(&self, out: &mut Vec<u8>) -> usize {
+        match self {
+            Self::AnnexB => out.put_slice(&Self::START_CODE),
+            Self::Avcc => out.put_u32(0),
+        }
+        out.len()
+    }
+
+    /// Fill the length prefix of the NAL unit begun at offset
+    pub(super) fn end(&self, out: &mut [u8], offset: usize) {
+        if *self == Self::Avcc {
+            let len = (out.len() - offset) as u32;
+            out[offset - Self::AVCC_LEN_SIZE..offset].copy_from_slice(&len.to_be_bytes());
+        }
+    }
+}
+
+/// Reassembly of a NAL unit fragmented across packets, FU-A of H.264 or FU of H.265
+///
+/// The unit is written into out as it arrives; an unfinished unit is removed by abandon,
+/// so that the length prefix of AVCC never stays unfilled.
+pub(super) struct FuAssembler {
+    format: NaluFormat,
+    /// offset of the NAL unit header in out
+    offset: Option<usize>,
+}
+
+impl FuAssembler {
+
+    pub(super) fn new(format: NaluFormat) -> Self {
+        Self {
+            format,
+            offset: None,
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_active(&self) -> bool {
+        self.offset.is_some()
+    }
+
+    /// Begin a NAL unit with its header, the caller abandons any unfinished one first
+    pub(super) fn start(&mut self, out: &mut Vec<u8>, header: &[u8]) {
+        debug_assert!(self.offset.is_none(), "FU started twice");
+        self.offset = Some(self.format.begin(out));
+        out.extend_from_slice(header);
+    }
+
+    /// Append a fragment of the NAL unit in progress
+    pub(super) fn append(&self, out: &mut Vec<u8>, body: &[u8]) {
+        debug_assert!(self.offset.is_some(), "FU fragment without start");
+        out.extend_from_slice(body);
+    }
+
+    pub(super) fn end(&mut self, out: &mut [u8]) {
+        if let Some(offset) = self.offset.take() {
+            self.format.end(out, offset);
+        }
+    }
+
+    /// Remove the unfinished NAL unit from out, true if there was one
+    pub(super) fn abandon(&mut self, out: &mut Vec<u8>) -> bool {
+        match self.offset.take() {
+            Some(offset) => {
+                out.truncate(offset - self.format.prefix_len());
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+pub struct NaluIter<'a> {
+    buf: &'a [u8],
+    format: NaluFormat,
+}
+
+impl<'a> Iterator for NaluIter<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let nalu = match self.format {
+                NaluFormat::AnnexB => self.next_annexb()?,
+                NaluFormat::Avcc => self.next_avcc()?,
+            };
+
+            if !nalu.is_empty() {
+                return Some(nalu);
+            }
+        }
+    }
+}
+
+impl<'a> NaluIter<'a> {
+    fn next_annexb(&mut self) -> Option<&'a [u8]> {
+        let start = find_start_code(self.buf)?;
+        let body = &self.buf[start.1..];
+
+        match find_start_code(body) {
+            Some((next, _end)) => {
+                self.buf = &body[next..];
+                Some(trim_trailing_zeros(&body[..next]))
+            },
+            None => {
+                self.buf = &[];
+                Some(body)
+            },
+        }
+    }
+
+    fn next_avcc(&mut self) -> Option<&'a [u8]> {
+        let size = NaluFormat::AVCC_LEN_SIZE;
+        if self.buf.len() < size {
+            self.buf = &[];
+            return None;
+        }
+
+        let len = u32::from_be_bytes([self.buf[0], self.buf[1], self.buf[2], self.buf[3]]) as usize;
+        if self.buf.len() < size + len {
+            // truncated
+            self.buf = &[];
+            return None;
+        }
+
+        let nalu = &self.buf[size..size + len];
+        self.buf = &self.buf[size + len..];
+        Some(nalu)
+    }
+}
+
+/// Return (start, end) of the first 00 00 01
+fn find_start_code(buf: &[u8]) -> Option<(usize, usize)> {
+    buf.windows(3)
+        .position(|w| w == [0, 0, 1])
+        .map(|pos| (pos, pos + 3))
+}
+
+/// Zeros before 00 00 01 belong to a 4 bytes start code or trailing_zero_8bits
+fn trim_trailing_zeros(buf: &[u8]) -> &[u8] {
+    let len = buf.iter().rposition(|x| *x != 0).map(|x| x + 1).unwrap_or(0);
+    &buf[..len]
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::NaluFormat;
+
+    #[test]
+    fn test_nalu_iter() {
+        let annexb = [0, 0, 0, 1, 0x67, 1, 2, 0, 0, 1, 0x68, 3, 0, 0, 0, 1, 0x65, 4, 5, 0];
+        let nalus: Vec<_> = NaluFormat::AnnexB.iter(&annexb).collect();
+        assert_eq!(nalus, [&[0x67, 1, 2][..], &[0x68, 3][..], &[0x65, 4, 5, 0][..]]);
+
+        let mut avcc = Vec::new();
+        for nalu in nalus.iter() {
+            NaluFormat::Avcc.write(&mut avcc, nalu);
+        }
+        assert!(NaluFormat::Avcc.iter(&avcc).eq(nalus.iter().copied()));
+
+        // truncated
+        assert_eq!(NaluFormat::Avcc.iter(&avcc[..avcc.len() - 1]).count(), 2);
+    }
+}
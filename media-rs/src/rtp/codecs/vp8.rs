@@ -0,0 +1,501 @@
+//! https://datatracker.ietf.org/doc/html/rfc7741
+//!
+
+use std::{collections::VecDeque, fmt};
+
+use crate::rtp::{error::RtpError, RefRtpPacket, Seq, Timestamp};
+
+use super::writer::{sealed::WriteFrame, ExtFn, PacketWriter};
+
+
+/*
+    VP8 payload descriptor
+
+         0 1 2 3 4 5 6 7
+        +-+-+-+-+-+-+-+-+
+        |X|R|N|S|R| PID | (REQUIRED)
+        +-+-+-+-+-+-+-+-+
+   X:   |I|L|T|K| RSV   | (OPTIONAL)
+        +-+-+-+-+-+-+-+-+
+   I:   |M| PictureID   | (OPTIONAL)
+        +-+-+-+-+-+-+-+-+
+        |   PictureID   | (when M is 1)
+        +-+-+-+-+-+-+-+-+
+   L:   |   TL0PICIDX   | (OPTIONAL)
+        +-+-+-+-+-+-+-+-+
+   T/K: |TID|Y| KEYIDX  | (OPTIONAL)
+        +-+-+-+-+-+-+-+-+
+
+    VP8 payload header, at the start of partition 0
+
+        +-+-+-+-+-+-+-+-+
+        |Size0|H| VER |P|
+        +-+-+-+-+-+-+-+-+
+*/
+
+const X_BIT: u8 = 0b1000_0000;
+const N_BIT: u8 = 0b0010_0000;
+const S_BIT: u8 = 0b0001_0000;
+const I_BIT: u8 = 0b1000_0000;
+const L_BIT: u8 = 0b0100_0000;
+const T_BIT: u8 = 0b0010_0000;
+const K_BIT: u8 = 0b0001_0000;
+const M_BIT: u8 = 0b1000_0000;
+
+
+/// Payload descriptor and payload of a VP8 RTP packet
+#[derive(Clone, Copy)]
+pub struct RefVp8Payload<'a> {
+    buf: &'a [u8],
+    /// offsets of optional fields, 0 if absent
+    picture_id_offset: u8,
+    tl0_offset: u8,
+    tid_offset: u8,
+    descriptor_len: u8,
+}
+
+impl<'a> TryFrom<&'a [u8]> for RefVp8Payload<'a> {
+    type Error = RtpError;
+
+    fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(buf)
+    }
+}
+
+impl<'a> RefVp8Payload<'a> {
+
+    pub fn parse(buf: &'a [u8]) -> Result<Self, RtpError> {
+        let check = |len: usize, origin: &'static str| {
+            if buf.len() < len {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: len,
+                    actual: buf.len(),
+                    origin,
+                });
+            }
+            Ok(())
+        };
+
+        check(1, "VP8 descriptor")?;
+
+        let mut this = Self {
+            buf,
+            picture_id_offset: 0,
+            tl0_offset: 0,
+            tid_offset: 0,
+            descriptor_len: 1,
+        };
+
+        if buf[0] & X_BIT == 0 {
+            return Ok(this);
+        }
+
+        check(2, "VP8 descriptor extension")?;
+        let ext = buf[1];
+        let mut offset = 2;
+
+        if ext & I_BIT != 0 {
+            check(offset + 1, "VP8 PictureID")?;
+            this.picture_id_offset = offset as u8;
+            offset += if buf[offset] & M_BIT != 0 { 2 } else { 1 };
+            check(offset, "VP8 PictureID")?;
+        }
+
+        if ext & L_BIT != 0 {
+            check(offset + 1, "VP8 TL0PICIDX")?;
+            this.tl0_offset = offset as u8;
+            offset += 1;
+        }
+
+        if ext & (T_BIT | K_BIT) != 0 {
+            check(offset + 1, "VP8 TID/KEYIDX")?;
+            this.tid_offset = offset as u8;
+            offset += 1;
+        }
+
+        this.descriptor_len = offset as u8;
+        Ok(this)
+    }
+
+    /// Parse payload of the packet
+    #[inline]
+    pub fn from_packet(packet: &RefRtpPacket<'a>) -> Result<Self, RtpError> {
+        Self::parse(packet.payload())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    #[inline]
+    pub fn descriptor_len(&self) -> usize {
+        self.descriptor_len as usize
+    }
+
+    /// VP8 payload after descriptor
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        &self.buf[self.descriptor_len()..]
+    }
+
+    /// N bit, frame can be discarded without affecting others
+    #[inline]
+    pub fn non_reference(&self) -> bool {
+        self.buf[0] & N_BIT != 0
+    }
+
+    /// S bit, start of a partition
+    #[inline]
+    pub fn start_of_partition(&self) -> bool {
+        self.buf[0] & S_BIT != 0
+    }
+
+    #[inline]
+    pub fn partition_index(&self) -> u8 {
+        self.buf[0] & 0x07
+    }
+
+    /// First packet of a frame, start of partition 0
+    #[inline]
+    pub fn start_of_frame(&self) -> bool {
+        self.start_of_partition() && self.partition_index() == 0
+    }
+
+    /// PictureID in 7 or 15 bits
+    pub fn picture_id(&self) -> Option<u16> {
+        let offset = self.picture_id_offset as usize;
+        if offset == 0 {
+            return None;
+        }
+
+        let b = self.buf[offset];
+        if b & M_BIT != 0 {
+            Some(u16::from_be_bytes([b & 0x7F, self.buf[offset + 1]]))
+        } else {
+            Some(b as u16)
+        }
+    }
+
+    /// PictureID is 15 bits
+    #[inline]
+    pub fn picture_id_15bit(&self) -> bool {
+        self.picture_id_offset != 0 && self.buf[self.picture_id_offset as usize] & M_BIT != 0
+    }
+
+    #[inline]
+    pub fn tl0_pic_idx(&self) -> Option<u8> {
+        (self.tl0_offset != 0).then(|| self.buf[self.tl0_offset as usize])
+    }
+
+    /// Temporal layer index, if T bit set
+    #[inline]
+    pub fn tid(&self) -> Option<u8> {
+        self.tid_byte(T_BIT).map(|b| b >> 6)
+    }
+
+    /// Y bit, layer sync, if T bit set
+    #[inline]
+    pub fn layer_sync(&self) -> Option<bool> {
+        self.tid_byte(T_BIT).map(|b| b & 0x20 != 0)
+    }
+
+    /// Temporal key frame index, if K bit set
+    #[inline]
+    pub fn key_idx(&self) -> Option<u8> {
+        self.tid_byte(K_BIT).map(|b| b & 0x1F)
+    }
+
+    /// Key frame by the P bit of VP8 payload header, only in the first packet of frame
+    pub fn is_keyframe(&self) -> bool {
+        self.start_of_frame() && matches!(self.payload().first(), Some(b) if b & 0x01 == 0)
+    }
+
+    fn tid_byte(&self, flag: u8) -> Option<u8> {
+        if self.tid_offset == 0 || self.buf[1] & flag == 0 {
+            return None;
+        }
+        Some(self.buf[self.tid_offset as usize])
+    }
+}
+
+impl<'a> fmt::Debug for RefVp8Payload<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Vp8")
+            .field("N", &self.non_reference())
+            .field("S", &self.start_of_partition())
+            .field("PID", &self.partition_index())
+            .field("picture_id", &self.picture_id())
+            .field("tl0_pic_idx", &self.tl0_pic_idx())
+            .field("tid", &self.tid())
+            .field("key_idx", &self.key_idx())
+            .field("payload", &self.payload().len())
+            .finish()
+    }
+}
+
+
+/// Frame assembled from RTP packets of the same timestamp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vp8Frame {
+    pub timestamp: Timestamp,
+    pub picture_id: Option<u16>,
+    pub data: Vec<u8>,
+    pub keyframe: bool,
+    /// false if packet loss was detected, start of frame or marker bit was not seen
+    pub complete: bool,
+}
+
+/// Assemble frames on marker bit or timestamp change
+///
+/// Push packets in sequence order, then pop the finished frames.
+pub struct Vp8Assembler {
+    frames: VecDeque<Vp8Frame>,
+    current: Option<Vp8Frame>,
+    last_seq: Option<Seq>,
+}
+
+impl Default for Vp8Assembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vp8Assembler {
+
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+            current: None,
+            last_seq: None,
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Vp8Frame> {
+        self.frames.pop_front()
+    }
+
+    pub fn push(&mut self, packet: &RefRtpPacket<'_>) -> Result<(), RtpError> {
+        let header = packet.header();
+        let timestamp = header.timestamp();
+        let seq = header.seq();
+
+        let lost = matches!(self.last_seq, Some(last) if !last.precedes(seq));
+        self.last_seq = Some(seq);
+
+        let vp8 = match RefVp8Payload::from_packet(packet) {
+            Ok(vp8) => vp8,
+            Err(e) => {
+                if matches!(&self.current, Some(frame) if frame.timestamp != timestamp) {
+                    self.flush(false);
+                }
+                if let Some(frame) = self.current.as_mut() {
+                    frame.complete = false;
+                }
+                if header.mark_flag() {
+                    self.flush(true);
+                }
+                return Err(e);
+            },
+        };
+
+        if matches!(&self.current, Some(frame) if frame.timestamp != timestamp || vp8.start_of_frame()) {
+            self.flush(false);
+        }
+
+        let frame = self.current.get_or_insert_with(|| Vp8Frame {
+            timestamp,
+            picture_id: vp8.picture_id(),
+            data: Vec::new(),
+            keyframe: vp8.is_keyframe(),
+            complete: vp8.start_of_frame(),
+        });
+
+        if lost {
+            frame.complete = false;
+        }
+        frame.data.extend_from_slice(vp8.payload());
+
+        if header.mark_flag() {
+            self.flush(true);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, marker: bool) {
+        let Some(mut frame) = self.current.take() else {
+            return
+        };
+
+        frame.complete &= marker;
+
+        if !frame.data.is_empty() {
+            self.frames.push_back(frame);
+        }
+    }
+}
+
+
+/// Split frames into RTP packets no larger than MTU, with a descriptor of optional PictureID
+pub struct Vp8Packetizer {
+    /// next PictureID in 15 bits
+    picture_id: Option<u16>,
+    writer: PacketWriter,
+}
+
+impl Vp8Packetizer {
+
+    pub fn new(payload_type: u8, ssrc: u32, seq: Seq) -> Self {
+        Self {
+            picture_id: None,
+            writer: PacketWriter::new(payload_type, ssrc, seq),
+        }
+    }
+
+    /// Write 15 bits PictureID starting from picture_id, incremented for each frame
+    pub fn picture_id(&mut self, picture_id: Option<u16>) -> &mut Self {
+        self.picture_id = picture_id.map(|x| x & 0x7FFF);
+        self
+    }
+}
+
+impl WriteFrame for Vp8Packetizer {
+    #[inline]
+    fn writer(&self) -> &PacketWriter {
+        &self.writer
+    }
+
+    #[inline]
+    fn writer_mut(&mut self) -> &mut PacketWriter {
+        &mut self.writer
+    }
+
+    fn write_frame<E>(&mut self, timestamp: Timestamp, frame: &[u8], ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        let descriptor_len = if self.picture_id.is_some() { 4 } else { 1 };
+        let max_payload = self.writer.max_payload(descriptor_len + 1, "VP8 mtu")?;
+
+        let mut start = S_BIT;
+        for chunk in frame.chunks(max_payload - descriptor_len) {
+            let payload = self.writer.next_payload(timestamp, ext_fn)?;
+            match self.picture_id {
+                Some(picture_id) => {
+                    payload.push(X_BIT | start);
+                    payload.push(I_BIT);
+                    payload.extend_from_slice(&(picture_id | 0x8000).to_be_bytes());
+                },
+                None => payload.push(start),
+            }
+            payload.extend_from_slice(chunk);
+            start = 0;
+        }
+
+        self.writer.finish(timestamp, ext_fn)?;
+
+        if let Some(picture_id) = self.picture_id.as_mut() {
+            *picture_id = (*picture_id + 1) & 0x7FFF;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{codecs::test_util::{payload_250, roundtrip}, RefRtpPacket, Seq};
+
+    use super::{RefVp8Payload, Vp8Assembler, Vp8Packetizer};
+
+    #[test]
+    fn test_descriptor() {
+        // X, S, I with 15 bits PictureID, L, T with Y, K; TID(2) Y(1) KEYIDX(5)
+        let buf = [0x90, 0xF0, 0x81, 0x23, 0x05, (2 << 6) | (1 << 5) | 3, 0x00, 0xAA];
+        let vp8 = RefVp8Payload::parse(&buf).unwrap();
+        assert_eq!(vp8.descriptor_len(), 6);
+        assert!(vp8.start_of_frame());
+        assert!(!vp8.non_reference());
+        assert_eq!(vp8.picture_id(), Some(0x0123));
+        assert!(vp8.picture_id_15bit());
+        assert_eq!(vp8.tl0_pic_idx(), Some(5));
+        assert_eq!(vp8.tid(), Some(2));
+        assert_eq!(vp8.layer_sync(), Some(true));
+        assert_eq!(vp8.key_idx(), Some(3));
+        assert!(vp8.is_keyframe());
+        assert_eq!(vp8.payload(), &[0x00, 0xAA]);
+
+        // 7 bits PictureID, K only, continuation of partition 1
+        let buf = [0xA1, 0x90, 0x12, 0x07, 0x01];
+        let vp8 = RefVp8Payload::parse(&buf).unwrap();
+        assert!(vp8.non_reference());
+        assert!(!vp8.start_of_frame());
+        assert_eq!(vp8.partition_index(), 1);
+        assert_eq!(vp8.picture_id(), Some(0x12));
+        assert_eq!(vp8.tid(), None);
+        assert_eq!(vp8.key_idx(), Some(7));
+        assert!(!vp8.is_keyframe());
+
+        // no extension, inter frame
+        let vp8 = RefVp8Payload::parse(&[0x10, 0x01]).unwrap();
+        assert_eq!(vp8.picture_id(), None);
+        assert!(!vp8.is_keyframe());
+
+        assert!(RefVp8Payload::parse(&[0x80]).is_err());
+        assert!(RefVp8Payload::parse(&[0x80, 0x80, 0x80]).is_err());
+        assert!(RefVp8Payload::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn test_packetize() {
+        // key frame
+        let mut frame = vec![0x00];
+        frame.extend(payload_250());
+
+        let mut packetizer = Vp8Packetizer::new(96, 1111, Seq(1));
+        packetizer.picture_id(Some(0x7FFF));
+
+        let mut assembler = Vp8Assembler::new();
+        let mut packets = roundtrip(&mut packetizer, 3000, &frame, |packet| assembler.push(packet));
+        packets.extend(roundtrip(&mut packetizer, 6000, &[0x01, 1, 2], |packet| assembler.push(packet)));
+        assert_eq!(packets.len(), 3 + 1);
+
+        let first = assembler.pop().unwrap();
+        assert!(first.complete && first.keyframe);
+        assert_eq!(first.picture_id, Some(0x7FFF));
+        assert_eq!(first.data, frame);
+
+        let second = assembler.pop().unwrap();
+        assert!(second.complete && !second.keyframe);
+        assert_eq!(second.picture_id, Some(0));
+        assert_eq!(second.data, [0x01, 1, 2]);
+
+        // lost first packet
+        let mut assembler = Vp8Assembler::new();
+        for packet in packets[1..3].iter() {
+            assembler.push(&RefRtpPacket::parse(packet).unwrap()).unwrap();
+        }
+        assert!(!assembler.pop().unwrap().complete);
+
+        // broken descriptor in the middle, X without extension byte
+        let mut broken = packets[1][..13].to_vec();
+        broken[12] = 0x80;
+        let mut assembler = Vp8Assembler::new();
+        assembler.push(&RefRtpPacket::parse(&packets[0]).unwrap()).unwrap();
+        assert!(assembler.push(&RefRtpPacket::parse(&broken).unwrap()).is_err());
+        assembler.push(&RefRtpPacket::parse(&packets[2]).unwrap()).unwrap();
+        assert!(!assembler.pop().unwrap().complete);
+
+        // broken descriptor with marker bit still ends the frame
+        let mut broken = packets[2][..13].to_vec();
+        broken[12] = 0x80;
+        let mut assembler = Vp8Assembler::new();
+        for packet in packets[..2].iter() {
+            assembler.push(&RefRtpPacket::parse(packet).unwrap()).unwrap();
+        }
+        assert!(assembler.push(&RefRtpPacket::parse(&broken).unwrap()).is_err());
+        assert!(!assembler.pop().unwrap().complete);
+        assembler.push(&RefRtpPacket::parse(&packets[3]).unwrap()).unwrap();
+        assert!(assembler.pop().unwrap().complete);
+    }
+}
@@ -0,0 +1,484 @@
+//! https://datatracker.ietf.org/doc/html/rfc9628
+//!
+
+use std::fmt;
+
+use crate::rtp::{error::RtpError, RefRtpPacket};
+
+
+/*
+    VP9 payload descriptor
+
+         0 1 2 3 4 5 6 7
+        +-+-+-+-+-+-+-+-+
+        |I|P|L|F|B|E|V|Z| (REQUIRED)
+        +-+-+-+-+-+-+-+-+
+   I:   |M| PICTURE ID  | (REQUIRED in non-flexible, optional in flexible)
+        +-+-+-+-+-+-+-+-+
+   M:   | EXTENDED PID  |
+        +-+-+-+-+-+-+-+-+
+   L:   | TID |U| SID |D| (CONDITIONALLY RECOMMENDED)
+        +-+-+-+-+-+-+-+-+
+        |   TL0PICIDX   | (non-flexible only)
+        +-+-+-+-+-+-+-+-+
+   P,F: | P_DIFF      |N| (flexible only, up to 3 times)
+        +-+-+-+-+-+-+-+-+
+   V:   | SS            |
+        | ..            |
+        +-+-+-+-+-+-+-+-+
+
+    Scalability structure (SS)
+
+        +-+-+-+-+-+-+-+-+
+   V:   | N_S |Y|G|-|-|-|
+        +-+-+-+-+-+-+-+-+
+   Y:   |     WIDTH     | (16 bits, N_S + 1 times)
+        |     HEIGHT    | (16 bits)
+        +-+-+-+-+-+-+-+-+
+   G:   |      N_G      |
+        +-+-+-+-+-+-+-+-+
+   N_G: | TID |U| R |-|-| (N_G times)
+        |    P_DIFF     | (R times)
+        +-+-+-+-+-+-+-+-+
+*/
+
+const I_BIT: u8 = 0b1000_0000;
+const P_BIT: u8 = 0b0100_0000;
+const L_BIT: u8 = 0b0010_0000;
+const F_BIT: u8 = 0b0001_0000;
+const B_BIT: u8 = 0b0000_1000;
+const E_BIT: u8 = 0b0000_0100;
+const V_BIT: u8 = 0b0000_0010;
+const Z_BIT: u8 = 0b0000_0001;
+const M_BIT: u8 = 0b1000_0000;
+const N_BIT: u8 = 0b0000_0001;
+
+const MAX_P_DIFF: usize = 3;
+
+#[inline]
+fn check(buf: &[u8], len: usize, origin: &'static str) -> Result<(), RtpError> {
+    if buf.len() < len {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: len,
+            actual: buf.len(),
+            origin,
+        });
+    }
+    Ok(())
+}
+
+
+/// Payload descriptor and payload of a VP9 RTP packet
+#[derive(Clone, Copy)]
+pub struct RefVp9Payload<'a> {
+    buf: &'a [u8],
+    /// offsets of optional fields, 0 if absent
+    picture_id_offset: u8,
+    layer_offset: u8,
+    p_diff_offset: u8,
+    p_diff_count: u8,
+    ss_offset: u16,
+    descriptor_len: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for RefVp9Payload<'a> {
+    type Error = RtpError;
+
+    fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::parse(buf)
+    }
+}
+
+impl<'a> RefVp9Payload<'a> {
+
+    pub fn parse(buf: &'a [u8]) -> Result<Self, RtpError> {
+        check(buf, 1, "VP9 descriptor")?;
+
+        let flags = buf[0];
+        let mut this = Self {
+            buf,
+            picture_id_offset: 0,
+            layer_offset: 0,
+            p_diff_offset: 0,
+            p_diff_count: 0,
+            ss_offset: 0,
+            descriptor_len: 1,
+        };
+        let mut offset = 1;
+
+        if flags & I_BIT != 0 {
+            check(buf, offset + 1, "VP9 PictureID")?;
+            this.picture_id_offset = offset as u8;
+            offset += if buf[offset] & M_BIT != 0 { 2 } else { 1 };
+            check(buf, offset, "VP9 PictureID")?;
+        }
+
+        if flags & L_BIT != 0 {
+            this.layer_offset = offset as u8;
+            // TL0PICIDX in non-flexible mode
+            offset += if flags & F_BIT == 0 { 2 } else { 1 };
+            check(buf, offset, "VP9 layer indices")?;
+        }
+
+        if flags & (F_BIT | P_BIT) == F_BIT | P_BIT {
+            this.p_diff_offset = offset as u8;
+            loop {
+                check(buf, offset + 1, "VP9 P_DIFF")?;
+                let more = buf[offset] & N_BIT != 0;
+                offset += 1;
+                this.p_diff_count += 1;
+
+                if !more {
+                    break;
+                }
+
+                if this.p_diff_count as usize == MAX_P_DIFF {
+                    return Err(RtpError::TooManyPDiff);
+                }
+            }
+        }
+
+        if flags & V_BIT != 0 {
+            this.ss_offset = offset as u16;
+            offset += RefVp9Ss::parse_len(&buf[offset..])?;
+        }
+
+        this.descriptor_len = offset as u16;
+        Ok(this)
+    }
+
+    /// Parse payload of the packet
+    #[inline]
+    pub fn from_packet(packet: &RefRtpPacket<'a>) -> Result<Self, RtpError> {
+        Self::parse(packet.payload())
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    #[inline]
+    pub fn descriptor_len(&self) -> usize {
+        self.descriptor_len as usize
+    }
+
+    /// VP9 payload after descriptor
+    #[inline]
+    pub fn payload(&self) -> &'a [u8] {
+        &self.buf[self.descriptor_len()..]
+    }
+
+    /// P bit, inter-picture predicted
+    #[inline]
+    pub fn inter_predicted(&self) -> bool {
+        self.buf[0] & P_BIT != 0
+    }
+
+    /// F bit, flexible mode
+    #[inline]
+    pub fn flexible(&self) -> bool {
+        self.buf[0] & F_BIT != 0
+    }
+
+    /// B bit, start of a layer frame
+    #[inline]
+    pub fn start_of_frame(&self) -> bool {
+        self.buf[0] & B_BIT != 0
+    }
+
+    /// E bit, end of a layer frame
+    #[inline]
+    pub fn end_of_frame(&self) -> bool {
+        self.buf[0] & E_BIT != 0
+    }
+
+    /// Z bit, not used as reference by upper spatial layers
+    #[inline]
+    pub fn not_upper_reference(&self) -> bool {
+        self.buf[0] & Z_BIT != 0
+    }
+
+    /// PictureID in 7 or 15 bits
+    pub fn picture_id(&self) -> Option<u16> {
+        let offset = self.picture_id_offset as usize;
+        if offset == 0 {
+            return None;
+        }
+
+        let b = self.buf[offset];
+        if b & M_BIT != 0 {
+            Some(u16::from_be_bytes([b & 0x7F, self.buf[offset + 1]]))
+        } else {
+            Some(b as u16)
+        }
+    }
+
+    /// PictureID is 15 bits
+    #[inline]
+    pub fn picture_id_15bit(&self) -> bool {
+        self.picture_id_offset != 0 && self.buf[self.picture_id_offset as usize] & M_BIT != 0
+    }
+
+    /// Temporal layer index, if L bit set
+    #[inline]
+    pub fn tid(&self) -> Option<u8> {
+        self.layer_byte().map(|b| b >> 5)
+    }
+
+    /// U bit, switching up point, if L bit set
+    #[inline]
+    pub fn switching_up_point(&self) -> Option<bool> {
+        self.layer_byte().map(|b| b & 0x10 != 0)
+    }
+
+    /// Spatial layer index, if L bit set
+    #[inline]
+    pub fn sid(&self) -> Option<u8> {
+        self.layer_byte().map(|b| (b >> 1) & 0x07)
+    }
+
+    /// D bit, inter-layer dependency, if L bit set
+    #[inline]
+    pub fn inter_layer_dependency(&self) -> Option<bool> {
+        self.layer_byte().map(|b| b & 0x01 != 0)
+    }
+
+    /// TL0PICIDX, in non-flexible mode with L bit set
+    #[inline]
+    pub fn tl0_pic_idx(&self) -> Option<u8> {
+        if self.layer_offset == 0 || self.flexible() {
+            return None;
+        }
+        Some(self.buf[self.layer_offset as usize + 1])
+    }
+
+    /// Differences of PictureID of reference pictures, in flexible mode
+    pub fn p_diff_iter(&self) -> impl Iterator<Item = u8> + 'a {
+        let offset = self.p_diff_offset as usize;
+        self.buf[offset..offset + self.p_diff_count as usize]
+            .iter()
+            .map(|b| b >> 1)
+    }
+
+    /// Scalability structure, if V bit set
+    pub fn scalability_structure(&self) -> Option<RefVp9Ss<'a>> {
+        if self.ss_offset == 0 {
+            return None;
+        }
+        Some(RefVp9Ss { buf: &self.buf[self.ss_offset as usize..self.descriptor_len()] })
+    }
+
+    /// First packet of a non inter-predicted frame at the base spatial layer
+    pub fn is_keyframe(&self) -> bool {
+        !self.inter_predicted() && self.start_of_frame() && self.sid().unwrap_or(0) == 0
+    }
+
+    #[inline]
+    fn layer_byte(&self) -> Option<u8> {
+        (self.layer_offset != 0).then(|| self.buf[self.layer_offset as usize])
+    }
+}
+
+impl<'a> fmt::Debug for RefVp9Payload<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Vp9")
+            .field("P", &self.inter_predicted())
+            .field("F", &self.flexible())
+            .field("B", &self.start_of_frame())
+            .field("E", &self.end_of_frame())
+            .field("Z", &self.not_upper_reference())
+            .field("picture_id", &self.picture_id())
+            .field("tid", &self.tid())
+            .field("sid", &self.sid())
+            .field("tl0_pic_idx", &self.tl0_pic_idx())
+            .field("p_diff", &self.p_diff_iter().collect::<Vec<_>>())
+            .field("ss", &self.scalability_structure())
+            .field("payload", &self.payload().len())
+            .finish()
+    }
+}
+
+
+/// Scalability structure, usually sent with the first packet of keyframe
+#[derive(Clone, Copy)]
+pub struct RefVp9Ss<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> RefVp9Ss<'a> {
+    const Y_BIT: u8 = 0b0001_0000;
+    const G_BIT: u8 = 0b0000_1000;
+
+    fn parse_len(buf: &[u8]) -> Result<usize, RtpError> {
+        check(buf, 1, "VP9 SS")?;
+
+        let mut len = 1;
+        if buf[0] & Self::Y_BIT != 0 {
+            len += 4 * ((buf[0] >> 5) as usize + 1);
+            check(buf, len, "VP9 SS resolutions")?;
+        }
+
+        if buf[0] & Self::G_BIT != 0 {
+            check(buf, len + 1, "VP9 SS N_G")?;
+            let groups = buf[len];
+            len += 1;
+
+            for _ in 0..groups {
+                check(buf, len + 1, "VP9 SS picture group")?;
+                len += 1 + ((buf[len] >> 2) & 0x03) as usize;
+                check(buf, len, "VP9 SS picture group")?;
+            }
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// N_S + 1
+    #[inline]
+    pub fn spatial_layers(&self) -> u8 {
+        (self.buf[0] >> 5) + 1
+    }
+
+    /// (width, height) of each spatial layer, if Y bit set
+    pub fn resolution_iter(&self) -> impl Iterator<Item = (u16, u16)> + 'a {
+        let len = if self.buf[0] & Self::Y_BIT != 0 { 4 * self.spatial_layers() as usize } else { 0 };
+        self.buf[1..1 + len]
+            .chunks_exact(4)
+            .map(|x| (u16::from_be_bytes([x[0], x[1]]), u16::from_be_bytes([x[2], x[3]])))
+    }
+
+    /// Pictures of the group of pictures, if G bit set
+    pub fn picture_group_iter(&self) -> Vp9PictureGroupIter<'a> {
+        if self.buf[0] & Self::G_BIT == 0 {
+            return Vp9PictureGroupIter { buf: &[], remains: 0 };
+        }
+
+        let offset = 1 + self.resolution_iter().count() * 4;
+        Vp9PictureGroupIter {
+            buf: &self.buf[offset + 1..],
+            remains: self.buf[offset],
+        }
+    }
+}
+
+impl<'a> fmt::Debug for RefVp9Ss<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Vp9Ss")
+            .field("spatial_layers", &self.spatial_layers())
+            .field("resolutions", &self.resolution_iter().collect::<Vec<_>>())
+            .field("picture_group", &self.picture_group_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Picture description in the group of pictures of SS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vp9Picture<'a> {
+    pub tid: u8,
+    pub switching_up_point: bool,
+    /// differences of PictureID of reference pictures
+    pub p_diffs: &'a [u8],
+}
+
+pub struct Vp9PictureGroupIter<'a> {
+    buf: &'a [u8],
+    remains: u8,
+}
+
+impl<'a> Iterator for Vp9PictureGroupIter<'a> {
+    type Item = Vp9Picture<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remains == 0 {
+            return None;
+        }
+        self.remains -= 1;
+
+        // checked in parse
+        let b = self.buf[0];
+        let refs = ((b >> 2) & 0x03) as usize;
+        let picture = Vp9Picture {
+            tid: b >> 5,
+            switching_up_point: b & 0x10 != 0,
+            p_diffs: &self.buf[1..1 + refs],
+        };
+        self.buf = &self.buf[1 + refs..];
+        Some(picture)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{RefVp9Payload, Vp9Picture};
+
+    #[test]
+    fn test_non_flexible() {
+        // I with 15 bits PictureID, L, B, V; TID 0, SID 0, TL0PICIDX 9
+        // SS of 2 spatial layers with resolutions, 2 pictures in group
+        let buf = [
+            0b1010_1010, 0x80, 0x10,
+            // TID(3) U(1) SID(3) D(1)
+            0x00, 9,
+            // N_S(3) Y(1) G(1)
+            (1 << 5) | (1 << 4) | (1 << 3), 0x01, 0x40, 0x00, 0xB4, 0x02, 0x80, 0x01, 0x68,
+            // N_G, then TID(3) U(1) R(2) and P_DIFF of each picture
+            2, (1 << 4) | (1 << 2), 4, (1 << 5) | (1 << 4) | (1 << 2), 1,
+            0xAA,
+        ];
+        let vp9 = RefVp9Payload::parse(&buf).unwrap();
+        assert_eq!(vp9.descriptor_len(), buf.len() - 1);
+        assert_eq!(vp9.payload(), &[0xAA]);
+        assert!(!vp9.flexible() && !vp9.inter_predicted());
+        assert!(vp9.start_of_frame() && !vp9.end_of_frame());
+        assert!(vp9.is_keyframe());
+        assert_eq!(vp9.picture_id(), Some(0x10));
+        assert!(vp9.picture_id_15bit());
+        assert_eq!(vp9.tid(), Some(0));
+        assert_eq!(vp9.sid(), Some(0));
+        assert_eq!(vp9.tl0_pic_idx(), Some(9));
+        assert_eq!(vp9.p_diff_iter().count(), 0);
+
+        let ss = vp9.scalability_structure().unwrap();
+        assert_eq!(ss.spatial_layers(), 2);
+        assert!(ss.resolution_iter().eq([(320, 180), (640, 360)]));
+        assert!(ss.picture_group_iter().eq([
+            Vp9Picture { tid: 0, switching_up_point: true, p_diffs: &[4] },
+            Vp9Picture { tid: 1, switching_up_point: true, p_diffs: &[1] },
+        ]));
+
+        // truncated SS
+        assert!(RefVp9Payload::parse(&buf[..10]).is_err());
+    }
+
+    #[test]
+    fn test_flexible() {
+        // I with 7 bits PictureID, P, L, F, E, Z; TID 2, U, SID 1, D; two P_DIFF
+        let buf = [0b1111_0101, 0x05, (2 << 5) | (1 << 4) | (1 << 1) | 1, (1 << 1) | 1, 2 << 1, 0xAA, 0xBB];
+        let vp9 = RefVp9Payload::parse(&buf).unwrap();
+        assert!(vp9.flexible() && vp9.inter_predicted());
+        assert!(!vp9.start_of_frame() && vp9.end_of_frame());
+        assert!(vp9.not_upper_reference());
+        assert!(!vp9.is_keyframe());
+        assert_eq!(vp9.picture_id(), Some(5));
+        assert!(!vp9.picture_id_15bit());
+        assert_eq!(vp9.tid(), Some(2));
+        assert_eq!(vp9.switching_up_point(), Some(true));
+        assert_eq!(vp9.sid(), Some(1));
+        assert_eq!(vp9.inter_layer_dependency(), Some(true));
+        assert_eq!(vp9.tl0_pic_idx(), None);
+        assert!(vp9.p_diff_iter().eq([1, 2]));
+        assert!(vp9.scalability_structure().is_none());
+        assert_eq!(vp9.payload(), &[0xAA, 0xBB]);
+
+        // more than 3 P_DIFF
+        assert!(RefVp9Payload::parse(&[0b0101_0000, 3, 3, 3, 3]).is_err());
+        assert!(RefVp9Payload::parse(&[0b0101_0000, 3]).is_err());
+        assert!(RefVp9Payload::parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::rtp::{error::RtpError, PayloadBuilder, RefRtpHeader, RtpBuilder, Seq, Timestamp};
+
+
+/// Writes extensions of a packet, gets the marker bit of the packet
+///
+/// The packet buffer is no larger than MTU, extensions should be written with
+/// checked builder methods like `try_write_ext`.
+pub trait ExtFn: for<'b> FnMut(RtpBuilder<'b>, bool) -> Result<PayloadBuilder<'b>, RtpError> {}
+
+impl<T> ExtFn for T where T: for<'b> FnMut(RtpBuilder<'b>, bool) -> Result<PayloadBuilder<'b>, RtpError> {}
+
+
+/// Settings and entry points shared by the packetizers of all codecs
+pub trait Packetizer: sealed::WriteFrame {
+    /// Max length of RTP packet including header, default 1200
+    fn mtu(&mut self, mtu: usize) -> &mut Self {
+        self.writer_mut().mtu = mtu;
+        self
+    }
+
+    /// Bytes reserved for header extensions in each packet, including extension header
+    fn ext_budget(&mut self, ext_budget: usize) -> &mut Self {
+        self.writer_mut().ext_budget = ext_budget;
+        self
+    }
+
+    /// Sequence number of the next packet
+    #[inline]
+    fn next_seq(&self) -> Seq {
+        self.writer().seq
+    }
+
+    /// Packetize a frame, or an access unit of H.264 and H.265, without header extensions
+    fn packetize<F>(&mut self, timestamp: Timestamp, frame: &[u8], emit: F) -> Result<(), RtpError>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.packetize_with_ext(timestamp, frame, |builder, _mark| Ok(builder.payload_builder()), emit)
+    }
+
+    /// Packetize a frame, ext_fn writes extensions of each packet within the budget
+    ///
+    /// ext_fn gets the marker bit of the packet, emit gets each finished packet.
+    /// Packets are emitted after the whole frame is written, so on error nothing is emitted
+    /// and the sequence number is kept.
+    fn packetize_with_ext<E, F>(&mut self, timestamp: Timestamp, frame: &[u8], mut ext_fn: E, mut emit: F) -> Result<(), RtpError>
+    where
+        E: for<'b> FnMut(RtpBuilder<'b>, bool) -> Result<PayloadBuilder<'b>, RtpError>,
+        F: FnMut(&[u8]),
+    {
+        let seq = self.next_seq();
+        self.writer_mut().reset();
+
+        let r = self.write_frame(timestamp, frame, &mut ext_fn);
+
+        let writer = self.writer_mut();
+        match r {
+            Ok(()) => writer.packets().for_each(&mut emit),
+            Err(_) => writer.seq = seq,
+        }
+        writer.reset();
+        r
+    }
+}
+
+impl<T: sealed::WriteFrame> Packetizer for T {}
+
+pub(super) mod sealed {
+    use crate::rtp::{error::RtpError, Timestamp};
+
+    use super::{ExtFn, PacketWriter};
+
+    /// Codec specific part of packetizers, not implementable outside
+    pub trait WriteFrame {
+        fn writer(&self) -> &PacketWriter;
+
+        fn writer_mut(&mut self) -> &mut PacketWriter;
+
+        /// Write packets of a frame through writer, the last one with marker bit
+        ///
+        /// Codec state should be kept on error, as the packets are dropped.
+        fn write_frame<E>(&mut self, timestamp: Timestamp, frame: &[u8], ext_fn: &mut E) -> Result<(), RtpError>
+        where
+            E: ExtFn;
+    }
+}
+
+
+/// Common part of packetizers: header fields, MTU and the packets of the frame being written
+pub struct PacketWriter {
+    pub(super) mtu: usize,
+    pub(super) ext_budget: usize,
+    payload_type: u8,
+    ssrc: u32,
+    pub(super) seq: Seq,
+    /// finished packets of the frame, back to back
+    packets: Vec<u8>,
+    /// end offset of each finished packet
+    ends: Vec<usize>,
+    /// payload of the packet not written yet, for marking the last one
+    pending: Vec<u8>,
+}
+
+impl PacketWriter {
+    pub(super) const DEFAULT_MTU: usize = 1200;
+
+    pub(super) fn new(payload_type: u8, ssrc: u32, seq: Seq) -> Self {
+        Self {
+            mtu: Self::DEFAULT_MTU,
+            ext_budget: 0,
+            payload_type,
+            ssrc,
+            seq,
+            packets: Vec::new(),
+            ends: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Max payload length, checked to be at least min_payload
+    pub(super) fn max_payload(&self, min_payload: usize, origin: &'static str) -> Result<usize, RtpError> {
+        let overhead = RefRtpHeader::MIN_LEN + self.ext_budget;
+        if self.mtu < overhead + min_payload {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: overhead + min_payload,
+                actual: self.mtu,
+                origin,
+            });
+        }
+        Ok(self.mtu - overhead)
+    }
+
+    /// Write pending payload if any, return the empty buffer of next payload
+    pub(super) fn next_payload<E>(&mut self, timestamp: Timestamp, ext_fn: &mut E) -> Result<&mut Vec<u8>, RtpError>
+    where
+        E: ExtFn,
+    {
+        if !self.pending.is_empty() {
+            self.write(timestamp, false, ext_fn)?;
+        }
+        Ok(&mut self.pending)
+    }
+
+    /// Write pending payload with marker bit
+    pub(super) fn finish<E>(&mut self, timestamp: Timestamp, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        if !self.pending.is_empty() {
+            self.write(timestamp, true, ext_fn)?;
+        }
+        Ok(())
+    }
+
+    /// Finished packets in order
+    fn packets(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        let starts = std::iter::once(0).chain(self.ends.iter().copied());
+        starts.zip(self.ends.iter()).map(|(start, end)| &self.packets[start..*end])
+    }
+
+    /// Drop packets and pending payload of the last frame
+    fn reset(&mut self) {
+        self.packets.clear();
+        self.ends.clear();
+        self.pending.clear();
+    }
+
+    fn write<E>(&mut self, timestamp: Timestamp, mark: bool, ext_fn: &mut E) -> Result<(), RtpError>
+    where
+        E: ExtFn,
+    {
+        // room for the extension header even if the budget is smaller, checked below
+        let start = self.packets.len();
+        let len = self.mtu.max(RefRtpHeader::MIN_LEN + 4);
+        self.packets.resize(start + len, 0);
+
+        let builder = RtpBuilder::from_basic(
+            &mut self.packets[start..],
+            mark,
+            self.payload_type,
+            self.seq,
+            timestamp,
+            self.ssrc,
+            [].into_iter(),
+        );
+
+        let builder = ext_fn(builder, mark)?;
+        let header_len = builder.header_len();
+        if header_len > RefRtpHeader::MIN_LEN + self.ext_budget {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: header_len,
+                actual: RefRtpHeader::MIN_LEN + self.ext_budget,
+                origin: "extension budget",
+            });
+        }
+
+        let len = builder.payload(&self.pending, false);
+        self.packets.truncate(start + len);
+        self.ends.push(self.packets.len());
+        self.pending.clear();
+        self.seq = self.seq.next();
+        Ok(())
+    }
+}
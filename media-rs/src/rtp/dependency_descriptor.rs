@@ -0,0 +1,436 @@
+//! https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension
+//!
+
+use std::sync::Arc;
+
+use super::{error::RtpError, extmap::ExtMap, RefRtpPacket};
+
+
+/// Decode target indication
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dti {
+    NotPresent = 0,
+    Discardable = 1,
+    Switch = 2,
+    Required = 3,
+}
+
+impl Dti {
+    fn from_bits(v: u32) -> Self {
+        match v & 0x03 {
+            0 => Self::NotPresent,
+            1 => Self::Discardable,
+            2 => Self::Switch,
+            _ => Self::Required,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FrameDependencyTemplate {
+    pub spatial_id: u8,
+    pub temporal_id: u8,
+    pub dtis: Vec<Dti>,
+    pub fdiffs: Vec<u16>,
+    pub chain_fdiffs: Vec<u8>,
+}
+
+/// Template dependency structure, persists across packets until replaced
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FrameDependencyStructure {
+    pub template_id_offset: u8,
+    pub decode_target_count: u8,
+    pub chain_count: u8,
+    pub decode_target_protected_by: Vec<u8>,
+    pub templates: Vec<FrameDependencyTemplate>,
+    /// (width, height) for each spatial layer, empty if not present
+    pub resolutions: Vec<(u16, u16)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyDescriptor {
+    pub start_of_frame: bool,
+    pub end_of_frame: bool,
+    pub template_id: u8,
+    pub frame_number: u16,
+    /// A new structure attached in this packet
+    pub structure_attached: bool,
+    /// Bit i for decode target i
+    pub active_decode_targets: u32,
+    /// Frame dependency resolved from template and custom fields
+    pub frame: FrameDependencyTemplate,
+    pub resolution: Option<(u16, u16)>,
+}
+
+/// Stateful parser, keeps the latest template structure and active decode targets
+#[derive(Debug, Clone, Default)]
+pub struct DependencyDescriptorReader {
+    structure: Option<Arc<FrameDependencyStructure>>,
+    active_decode_targets: u32,
+}
+
+impl DependencyDescriptorReader {
+
+    pub const URI: &'static str = "https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension";
+
+    const MANDATORY_LEN: usize = 3;
+    const MAX_TEMPLATES: usize = 64;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn structure(&self) -> Option<&Arc<FrameDependencyStructure>> {
+        self.structure.as_ref()
+    }
+
+    /// Find the extension by uri and parse it, None if absent
+    pub fn parse_packet(
+        &mut self,
+        packet: &RefRtpPacket<'_>,
+        map: &ExtMap,
+    ) -> Result<Option<DependencyDescriptor>, RtpError> {
+        match packet.ext_by_uri(map, Self::URI) {
+            Some(data) => self.parse(data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// State is updated only if data is parsed successfully
+    pub fn parse(&mut self, data: &[u8]) -> Result<DependencyDescriptor, RtpError> {
+        if data.len() < Self::MANDATORY_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::MANDATORY_LEN,
+                actual: data.len(),
+                origin: "Dependency descriptor length",
+            });
+        }
+
+        let mut r = BitReader::new(data);
+
+        // mandatory_descriptor_fields
+        let start_of_frame = r.read_bit()?;
+        let end_of_frame = r.read_bit()?;
+        let template_id = r.read(6)? as u8;
+        let frame_number = r.read(16)? as u16;
+
+        let mut structure = self.structure.clone();
+        let mut active_decode_targets = self.active_decode_targets;
+        let mut structure_attached = false;
+        let mut custom_dtis = false;
+        let mut custom_fdiffs = false;
+        let mut custom_chains = false;
+
+        // extended_descriptor_fields
+        if data.len() > Self::MANDATORY_LEN {
+            let structure_present = r.read_bit()?;
+            let active_present = r.read_bit()?;
+            custom_dtis = r.read_bit()?;
+            custom_fdiffs = r.read_bit()?;
+            custom_chains = r.read_bit()?;
+
+            if structure_present {
+                let s = read_structure(&mut r)?;
+                active_decode_targets = full_mask(s.decode_target_count);
+                structure = Some(Arc::new(s));
+                structure_attached = true;
+            }
+
+            if active_present {
+                let s = structure.as_ref().ok_or(RtpError::MissingDependencyStructure)?;
+                active_decode_targets = r.read(s.decode_target_count as u32)?;
+            }
+        }
+
+        let s = structure.ok_or(RtpError::MissingDependencyStructure)?;
+
+        // frame_dependency_definition
+        let index = (template_id as usize + Self::MAX_TEMPLATES - s.template_id_offset as usize) % Self::MAX_TEMPLATES;
+        let Some(template) = s.templates.get(index) else {
+            return Err(RtpError::InvalidDependencyTemplate(template_id));
+        };
+        let mut frame = template.clone();
+
+        if custom_dtis {
+            frame.dtis = (0..s.decode_target_count)
+                .map(|_| r.read(2).map(Dti::from_bits))
+                .collect::<Result<_, _>>()?;
+        }
+
+        if custom_fdiffs {
+            frame.fdiffs.clear();
+            loop {
+                let size = r.read(2)?;
+                if size == 0 {
+                    break;
+                }
+                frame.fdiffs.push(r.read(4 * size)? as u16 + 1);
+            }
+        }
+
+        if custom_chains {
+            frame.chain_fdiffs = (0..s.chain_count)
+                .map(|_| r.read(8).map(|v| v as u8))
+                .collect::<Result<_, _>>()?;
+        }
+
+        let resolution = s.resolutions.get(frame.spatial_id as usize).copied();
+
+        self.structure = Some(s);
+        self.active_decode_targets = active_decode_targets;
+
+        Ok(DependencyDescriptor {
+            start_of_frame,
+            end_of_frame,
+            template_id,
+            frame_number,
+            structure_attached,
+            active_decode_targets,
+            frame,
+            resolution,
+        })
+    }
+}
+
+#[inline]
+fn full_mask(count: u8) -> u32 {
+    if count as u32 >= u32::BITS {
+        u32::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+fn read_structure(r: &mut BitReader<'_>) -> Result<FrameDependencyStructure, RtpError> {
+    let template_id_offset = r.read(6)? as u8;
+    let dt_cnt = r.read(5)? + 1;
+
+    // template_layers
+    let mut templates = Vec::new();
+    let mut spatial_id = 0_u8;
+    let mut temporal_id = 0_u8;
+    loop {
+        if templates.len() >= DependencyDescriptorReader::MAX_TEMPLATES {
+            return Err(RtpError::InvalidDependencyTemplate(templates.len() as u8));
+        }
+
+        templates.push(FrameDependencyTemplate {
+            spatial_id,
+            temporal_id,
+            ..Default::default()
+        });
+
+        match r.read(2)? {
+            0 => {},
+            1 => temporal_id += 1,
+            2 => {
+                temporal_id = 0;
+                spatial_id += 1;
+            },
+            _ => break,
+        }
+    }
+
+    // template_dtis
+    for template in templates.iter_mut() {
+        template.dtis = (0..dt_cnt)
+            .map(|_| r.read(2).map(Dti::from_bits))
+            .collect::<Result<_, _>>()?;
+    }
+
+    // template_fdiffs
+    for template in templates.iter_mut() {
+        while r.read_bit()? {
+            template.fdiffs.push(r.read(4)? as u16 + 1);
+        }
+    }
+
+    // template_chains
+    let chain_cnt = r.read_ns(dt_cnt + 1)?;
+    let mut decode_target_protected_by = Vec::new();
+    if chain_cnt > 0 {
+        for _ in 0..dt_cnt {
+            decode_target_protected_by.push(r.read_ns(chain_cnt)? as u8);
+        }
+        for template in templates.iter_mut() {
+            template.chain_fdiffs = (0..chain_cnt)
+                .map(|_| r.read(4).map(|v| v as u8))
+                .collect::<Result<_, _>>()?;
+        }
+    }
+
+    // render_resolutions
+    let mut resolutions = Vec::new();
+    if r.read_bit()? {
+        for _ in 0..=spatial_id {
+            let width = r.read(16)? as u16;
+            let height = r.read(16)? as u16;
+            resolutions.push((width.wrapping_add(1), height.wrapping_add(1)));
+        }
+    }
+
+    Ok(FrameDependencyStructure {
+        template_id_offset,
+        decode_target_count: dt_cnt as u8,
+        chain_count: chain_cnt as u8,
+        decode_target_protected_by,
+        templates,
+        resolutions,
+    })
+}
+
+
+
+/// Most significant bit first
+struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn read_bit(&mut self) -> Result<bool, RtpError> {
+        self.read(1).map(|v| v != 0)
+    }
+
+    /// bits up to 32
+    fn read(&mut self, bits: u32) -> Result<u32, RtpError> {
+        let end = self.pos + bits as usize;
+        if end > self.buf.len() * 8 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: end.div_ceil(8),
+                actual: self.buf.len(),
+                origin: "Dependency descriptor bits",
+            });
+        }
+
+        let mut v = 0_u32;
+        for _ in 0..bits {
+            let bit = (self.buf[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            v = v << 1 | bit as u32;
+            self.pos += 1;
+        }
+        Ok(v)
+    }
+
+    /// Non-symmetric unsigned value in [0, n)
+    fn read_ns(&mut self, n: u32) -> Result<u32, RtpError> {
+        let w = u32::BITS - n.leading_zeros();
+        let m = (1 << w) - n;
+        let v = self.read(w - 1)?;
+        if v < m {
+            return Ok(v);
+        }
+        let extra_bit = self.read(1)?;
+        Ok((v << 1) - m + extra_bit)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::error::RtpError;
+
+    use super::{DependencyDescriptorReader, Dti};
+
+    #[derive(Default)]
+    struct BitWriter {
+        buf: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, v: u32, n: u32) -> &mut Self {
+            for i in (0..n).rev() {
+                if self.bits.is_multiple_of(8) {
+                    self.buf.push(0);
+                }
+                let bit = ((v >> i) & 1) as u8;
+                *self.buf.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+                self.bits += 1;
+            }
+            self
+        }
+    }
+
+    /// L1T2, two decode targets, one chain
+    fn l1t2_with_structure() -> Vec<u8> {
+        let mut w = BitWriter::default();
+        w.write(1, 1).write(0, 1).write(2, 6).write(100, 16);
+        // structure present, active absent, no custom
+        w.write(0b10000, 5);
+        // template_id_offset = 2, dt_cnt - 1 = 1
+        w.write(2, 6).write(1, 5);
+        // templates: (s0,t0) (s0,t0) (s0,t1)
+        w.write(0, 2).write(1, 2).write(3, 2);
+        // dtis
+        w.write(0b1111, 4).write(0b1111, 4).write(0b0001, 4);
+        // fdiffs: none / [2] / [1]
+        w.write(0, 1);
+        w.write(1, 1).write(1, 4).write(0, 1);
+        w.write(1, 1).write(0, 4).write(0, 1);
+        // chain_cnt = ns(3) -> 1, coded as '01'
+        w.write(0b10, 2);
+        // protected_by ns(1) takes no bit, chain fdiffs
+        w.write(0, 4).write(2, 4).write(1, 4);
+        // resolutions
+        w.write(1, 1).write(639, 16).write(359, 16);
+        w.buf
+    }
+
+    #[test]
+    fn test_parse_dd() {
+        let mut reader = DependencyDescriptorReader::new();
+
+        // no structure yet
+        let mut w = BitWriter::default();
+        w.write(1, 1).write(1, 1).write(3, 6).write(101, 16);
+        assert!(matches!(reader.parse(&w.buf), Err(RtpError::MissingDependencyStructure)));
+
+        let dd = reader.parse(&l1t2_with_structure()).unwrap();
+        assert!(dd.start_of_frame);
+        assert!(!dd.end_of_frame);
+        assert_eq!(dd.frame_number, 100);
+        assert!(dd.structure_attached);
+        assert_eq!(dd.active_decode_targets, 0b11);
+        assert_eq!(dd.frame.dtis, [Dti::Required, Dti::Required]);
+        assert!(dd.frame.fdiffs.is_empty());
+        assert_eq!(dd.resolution, Some((640, 360)));
+
+        let s = reader.structure().unwrap();
+        assert_eq!(s.templates.len(), 3);
+        assert_eq!(s.chain_count, 1);
+        assert_eq!(s.decode_target_protected_by, [0, 0]);
+        assert_eq!(s.templates[2].temporal_id, 1);
+        assert_eq!(s.templates[2].chain_fdiffs, [1]);
+
+        // mandatory only, template 4 -> index 2
+        let mut w = BitWriter::default();
+        w.write(1, 1).write(1, 1).write(4, 6).write(101, 16);
+        let dd = reader.parse(&w.buf).unwrap();
+        assert!(!dd.structure_attached);
+        assert_eq!(dd.frame.temporal_id, 1);
+        assert_eq!(dd.frame.dtis, [Dti::NotPresent, Dti::Discardable]);
+        assert_eq!(dd.frame.fdiffs, [1]);
+
+        // custom fdiffs and active decode targets
+        let mut w = BitWriter::default();
+        w.write(1, 1).write(1, 1).write(3, 6).write(102, 16);
+        w.write(0b01010, 5);
+        w.write(0b01, 2);
+        w.write(2, 2).write(17, 8).write(1, 2).write(0, 4).write(0, 2);
+        let dd = reader.parse(&w.buf).unwrap();
+        assert_eq!(dd.active_decode_targets, 0b01);
+        assert_eq!(dd.frame.fdiffs, [18, 1]);
+
+        // template out of range
+        let mut w = BitWriter::default();
+        w.write(1, 1).write(1, 1).write(10, 6).write(103, 16);
+        assert!(matches!(reader.parse(&w.buf), Err(RtpError::InvalidDependencyTemplate(10))));
+    }
+}
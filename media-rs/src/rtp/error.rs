@@ -13,8 +13,44 @@ pub enum RtpError {
 
     UnknownPayloadType(u8),
 
+    UnexpectedRtcpType(u8),
+
+    CompoundFirstNotReport(u8),
+
+    UnexpectedPadding,
+
+    UnexpectedFeedbackFmt(u8),
+
+    InvalidTwccStatus(u8),
+
+    UnknownAfbIdentifier([u8; 4]),
+
+    InvalidSdesTerminator,
+
+    InvalidRtcpCount(u8),
+
+    InvalidRtcpLength(usize),
+
     UnknownExtFormat(u16),
 
-    InvalidPaddingLength(u8),    
+    InvalidExtMap,
+
+    InvalidExtLength(usize),
+
+    InvalidExtValue,
+
+    ExtNotFound(u8),
+
+    InvalidExtId(u8),
+
+    MissingDependencyStructure,
+
+    InvalidDependencyTemplate(u8),
+
+    InvalidPaddingLength(u8),
+
+    UnsupportedNalType(u8),
+
+    TooManyPDiff,    
 }
 
@@ -0,0 +1,177 @@
+use super::{error::RtpError, extension::ExtFormat, RefRtpHeader, RefRtpPacket, RtpBuilder};
+
+
+/// Edit list of header extensions, applied while copying a packet into another buffer
+///
+/// Kept elements stay in original order, set elements are appended after them.
+#[derive(Debug, Clone, Default)]
+pub struct ExtEdit<'e> {
+    drops: Vec<u8>,
+    sets: Vec<(u8, &'e [u8])>,
+    format: Option<ExtFormat>,
+}
+
+impl<'e> ExtEdit<'e> {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn drop_id(&mut self, id: u8) -> &mut Self {
+        self.drops.push(id);
+        self
+    }
+
+    /// Replace value of id, or add it if absent
+    pub fn set(&mut self, id: u8, value: &'e [u8]) -> &mut Self {
+        self.sets.retain(|(x, _value)| *x != id);
+        self.sets.push((id, value));
+        self
+    }
+
+    /// Output format, default to the format of input packet or OneByte if none
+    pub fn format(&mut self, format: ExtFormat) -> &mut Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Write the edited packet into out and return its length
+    ///
+    /// Payload is copied as is; if the input has padding, output is padded to 32 bits only.
+    pub fn apply(&self, packet: &RefRtpPacket<'_>, out: &mut [u8]) -> Result<usize, RtpError> {
+        let header = packet.header();
+
+        let kept = packet.extension_iter()
+            .into_iter()
+            .flatten()
+            .filter(|(id, _value)| !self.drops.contains(id) && !self.sets.iter().any(|(x, _v)| x == id));
+
+        let sets = self.sets.iter()
+            .filter(|(id, _value)| !self.drops.contains(id))
+            .copied();
+
+        let elems: Vec<_> = kept.chain(sets).collect();
+
+        let format = self.format
+            .or_else(|| packet.extension_range().map(|(format, _range)| format))
+            .unwrap_or(ExtFormat::OneByte);
+
+        for (id, value) in elems.iter() {
+            if *id == 0 || *id > format.max_id() {
+                return Err(RtpError::InvalidExtId(*id));
+            }
+            format.check_len(value.len())?;
+        }
+
+        let csrc_len = 4 * packet.csrc_iter().count();
+        let ext_len = if elems.is_empty() {
+            0
+        } else {
+            let body: usize = elems.iter().map(|(_id, value)| format.elem_header_len() + value.len()).sum();
+            4 + body.div_ceil(4) * 4
+        };
+        let mut expect = RefRtpHeader::MIN_LEN + csrc_len + ext_len + packet.payload().len();
+        let padding = packet.padding().is_some();
+        if padding {
+            expect = expect.div_ceil(4) * 4;
+        }
+
+        if out.len() < expect {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: out.len(),
+                origin: "RTP extension edit output",
+            });
+        }
+
+        let builder = RtpBuilder::from_basic(
+            out,
+            header.mark_flag(),
+            header.payload_type(),
+            header.seq(),
+            header.timestamp(),
+            header.ssrc(),
+            packet.csrc_iter(),
+        );
+
+        if elems.is_empty() {
+            return Ok(builder.payload(packet.payload(), padding));
+        }
+
+        let mut builder = builder.extension(format);
+        for (id, value) in elems {
+            builder.write_ext(id, value);
+        }
+        Ok(builder.payload(packet.payload(), padding))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, extension::ExtFormat, RefRtpPacket, RtpBuilder, Seq, Timestamp};
+
+    use super::ExtEdit;
+
+    #[test]
+    fn test_ext_edit() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtpBuilder::from_basic(&mut buf, true, 96, Seq(1), Timestamp(90), 1111, [2222].into_iter())
+            .extension(ExtFormat::OneByte);
+        builder.write_ext(1, &[0x01]);
+        builder.write_ext(2, &[0x02, 0x02]);
+        builder.write_ext(3, &[0x03, 0x03, 0x03]);
+        let len = builder.payload(&[1, 2, 3, 4, 5], false);
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+
+        let mut out = vec![0_u8; 1500];
+
+        // drop, replace and add
+        let out_len = ExtEdit::new()
+            .drop_id(1)
+            .set(3, &[0x33])
+            .set(5, &[0x05, 0x05])
+            .apply(&packet, &mut out)
+            .unwrap();
+        let edited = RefRtpPacket::parse(&out[..out_len]).unwrap();
+        let exts: Vec<_> = edited.extension_iter().unwrap().collect();
+        assert_eq!(exts, [(2, &[0x02, 0x02][..]), (3, &[0x33][..]), (5, &[0x05, 0x05][..])]);
+        assert_eq!(edited.header().seq(), Seq(1));
+        assert!(edited.header().mark_flag());
+        assert!(edited.csrc_iter().eq([2222]));
+        assert_eq!(edited.payload(), packet.payload());
+
+        // to two-byte with a large id and empty value
+        let out_len = ExtEdit::new()
+            .format(ExtFormat::TwoByte)
+            .set(200, &[])
+            .apply(&packet, &mut out)
+            .unwrap();
+        let edited = RefRtpPacket::parse(&out[..out_len]).unwrap();
+        assert_eq!(edited.extension_iter().unwrap().count(), 4);
+        assert_eq!(edited.ext_by_id(200), Some(&[][..]));
+
+        // ids beyond one-byte
+        assert!(matches!(
+            ExtEdit::new().set(15, &[1]).apply(&packet, &mut out),
+            Err(RtpError::InvalidExtId(15))
+        ));
+
+        // output too small
+        let out_len = ExtEdit::new().set(5, &[0x05, 0x05]).apply(&packet, &mut out).unwrap();
+        assert!(matches!(
+            ExtEdit::new().set(5, &[0x05, 0x05]).apply(&packet, &mut out[..out_len - 1]),
+            Err(RtpError::NotEnoughBuffer { .. })
+        ));
+        assert!(ExtEdit::new().set(5, &[0x05, 0x05]).apply(&packet, &mut out[..out_len]).is_ok());
+
+        // drop all
+        let out_len = ExtEdit::new()
+            .drop_id(1).drop_id(2).drop_id(3)
+            .apply(&packet, &mut out)
+            .unwrap();
+        let edited = RefRtpPacket::parse(&out[..out_len]).unwrap();
+        assert!(!edited.header().extension_flag());
+        assert_eq!(edited.payload(), &[1, 2, 3, 4, 5]);
+    }
+}
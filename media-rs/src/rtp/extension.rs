@@ -47,6 +47,52 @@ impl ExtFormat {
         }
     }
 
+    /// Same as iter but yield offsets in buf, used for in-place editing
+    pub(super) fn elem_iter<'a>(&self, buf: &'a [u8]) -> ExtElemIter<'a> {
+        match self {
+            Self::OneByte => ExtElemIter { buf, offset: 0, parse: parse_onebyte_uncheck },
+            Self::TwoByte => ExtElemIter { buf, offset: 0, parse: parse_twobyte_uncheck },
+        }
+    }
+
+    /// Check element body length could be written in this format
+    pub fn check_len(&self, len: usize) -> Result<(), RtpError> {
+        let valid = match self {
+            Self::OneByte => (1..=16).contains(&len),
+            Self::TwoByte => len <= 255,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(RtpError::InvalidExtLength(len))
+        }
+    }
+
+    /// Overwrite id of element at the beginning of buf, length is kept
+    pub(super) fn write_id(&self, buf: &mut [u8], id: u8) {
+        match self {
+            Self::OneByte => buf[0] = id << 4 | (buf[0] & 0x0F),
+            Self::TwoByte => buf[0] = id,
+        }
+    }
+
+    /// Length of element header in this format
+    pub(super) fn elem_header_len(&self) -> usize {
+        match self {
+            Self::OneByte => 1,
+            Self::TwoByte => 2,
+        }
+    }
+
+    /// Max id could be written in this format
+    pub fn max_id(&self) -> u8 {
+        match self {
+            Self::OneByte => 14,
+            Self::TwoByte => 255,
+        }
+    }
+
     pub fn build_fn(&self) -> WriteExtFns {
         match self {
             Self::OneByte => WriteExtFns {
@@ -206,6 +252,57 @@ impl<'a> Iterator for ExtIter<'a> {
 }
 
 
+/// Location of an extension element in extension buf
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ExtElem {
+    pub offset: usize,
+    pub header_len: usize,
+    pub id: u8,
+    pub body_len: usize,
+}
+
+impl ExtElem {
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.offset + self.header_len + self.body_len
+    }
+}
+
+pub(super) struct ExtElemIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    parse: ParseIdUncheckFn,
+}
+
+impl<'a> Iterator for ExtElemIter<'a> {
+    type Item = ExtElem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.buf.len() {
+            let buf = &self.buf[self.offset..];
+
+            if buf[0] == 0 {
+                // padding
+                self.offset += 1;
+                continue;
+            }
+
+            let (next, id, body_len) = (self.parse)(buf)?;
+
+            let elem = ExtElem {
+                offset: self.offset,
+                header_len: buf.len() - next.len(),
+                id,
+                body_len,
+            };
+            self.offset = elem.end();
+            return Some(elem)
+        }
+        None
+    }
+}
+
+
 // pub struct OneByteIter<'a>(&'a [u8]);
 
 // impl<'a> Iterator for OneByteIter<'a> {
@@ -0,0 +1,261 @@
+//! https://datatracker.ietf.org/doc/html/rfc8285#section-8
+//!
+
+use super::{error::RtpError, RefRtpPacket};
+
+
+/// Typed value of an extension identified by uri
+pub trait ExtValue<'a>: Sized {
+    const URI: &'static str;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError>;
+}
+
+
+/// Negotiated extension ids of a session, both directions of uri <-> id
+#[derive(Debug, Clone, Default)]
+pub struct ExtMap {
+    items: Vec<(u8, String)>,
+}
+
+impl ExtMap {
+
+    pub const SDP_PREFIX: &'static str = "extmap:";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from SDP lines, lines other than extmap are ignored
+    pub fn from_sdp_lines<'s, I>(lines: I) -> Result<Self, RtpError>
+    where
+        I: IntoIterator<Item = &'s str>,
+    {
+        let mut me = Self::new();
+        for line in lines {
+            let line = line.trim();
+            let line = line.strip_prefix("a=").unwrap_or(line);
+            if line.starts_with(Self::SDP_PREFIX) {
+                me.insert_sdp_line(line)?;
+            }
+        }
+        Ok(me)
+    }
+
+    /// Parse `a=extmap:<id>[/<direction>] <uri> [<attributes>]`, return id
+    pub fn insert_sdp_line(&mut self, line: &str) -> Result<u8, RtpError> {
+        let (id, uri) = parse_sdp_extmap(line)?;
+        self.insert(id.into(), uri)?;
+        Ok(id)
+    }
+
+    /// Return the old uri of id, id should be in 1..=255
+    pub fn insert(&mut self, id: u16, uri: &str) -> Result<Option<String>, RtpError> {
+        let id = match u8::try_from(id) {
+            Ok(id) if id > 0 => id,
+            _ => return Err(RtpError::InvalidExtMap),
+        };
+
+        match self.items.iter_mut().find(|(x, _uri)| *x == id) {
+            Some((_id, old)) => Ok(Some(std::mem::replace(old, uri.to_string()))),
+            None => {
+                self.items.push((id, uri.to_string()));
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: u8) -> Option<String> {
+        let index = self.items.iter().position(|(x, _uri)| *x == id)?;
+        Some(self.items.swap_remove(index).1)
+    }
+
+    pub fn id(&self, uri: &str) -> Option<u8> {
+        self.items.iter()
+            .find(|(_id, x)| x == uri)
+            .map(|(id, _uri)| *id)
+    }
+
+    pub fn uri(&self, id: u8) -> Option<&str> {
+        self.items.iter()
+            .find(|(x, _uri)| *x == id)
+            .map(|(_id, uri)| uri.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &str)> + '_ {
+        self.items.iter().map(|(id, uri)| (*id, uri.as_str()))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+fn parse_sdp_extmap(line: &str) -> Result<(u8, &str), RtpError> {
+    let line = line.trim();
+    let line = line.strip_prefix("a=").unwrap_or(line);
+    let line = line.strip_prefix(ExtMap::SDP_PREFIX).ok_or(RtpError::InvalidExtMap)?;
+
+    let mut fields = line.split_ascii_whitespace();
+    let value = fields.next().ok_or(RtpError::InvalidExtMap)?;
+    let uri = fields.next().ok_or(RtpError::InvalidExtMap)?;
+
+    // direction is not cared
+    let id = value.split('/').next().unwrap_or(value);
+    match id.parse::<u8>() {
+        Ok(id) if id > 0 => Ok((id, uri)),
+        _ => Err(RtpError::InvalidExtMap),
+    }
+}
+
+
+
+/// Rewrite extension ids of packets when forwarding from one extmap to another
+///
+/// Elements whose uri is absent in target, or whose new id doesn't fit
+/// the packet's extension format, are dropped by zeroing into padding.
+#[derive(Clone)]
+pub struct ExtRemap {
+    // 0 means drop
+    ids: [u8; 256],
+}
+
+impl ExtRemap {
+    pub fn new(from: &ExtMap, to: &ExtMap) -> Self {
+        let mut ids = [0_u8; 256];
+        for (id, uri) in from.iter() {
+            if let Some(new_id) = to.id(uri) {
+                ids[id as usize] = new_id;
+            }
+        }
+        Self { ids }
+    }
+
+    #[inline]
+    pub fn map_id(&self, id: u8) -> Option<u8> {
+        match self.ids[id as usize] {
+            0 => None,
+            new_id => Some(new_id),
+        }
+    }
+
+    /// Rewrite ids of RTP packet in buf in place, packet length is kept
+    pub fn apply(&self, buf: &mut [u8]) -> Result<(), RtpError> {
+        let packet = RefRtpPacket::parse(buf)?;
+        let Some((ext_fmt, range)) = packet.extension_range() else {
+            return Ok(())
+        };
+
+        let ext_buf = &mut buf[range];
+        let elems: Vec<_> = ext_fmt.elem_iter(ext_buf).collect();
+
+        for elem in elems {
+            match self.map_id(elem.id) {
+                Some(new_id) if new_id <= ext_fmt.max_id() => {
+                    ext_fmt.write_id(&mut ext_buf[elem.offset..], new_id);
+                },
+                _ => {
+                    ext_buf[elem.offset..elem.end()].fill(0);
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ExtRemap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries((0..=255_u8).filter_map(|id| self.map_id(id).map(|new_id| (id, new_id))))
+            .finish()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{
+        abs_send_time::AbsSendTimeValue, audio_level::AudioLevelValue, error::RtpError,
+        extension::ExtFormat, transport_cc::TransportCcValue, RefRtpPacket, RtpBuilder, Seq, Timestamp,
+    };
+
+    use super::{ExtMap, ExtRemap};
+
+    const SDP: &str = "\
+m=audio 9 UDP/TLS/RTP/SAVPF 111\r
+a=extmap:1 urn:ietf:params:rtp-hdrext:ssrc-audio-level vad=on\r
+a=extmap:3/sendrecv http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r
+a=extmap:5 http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01\r
+a=rtcp-mux\r
+";
+
+    fn build(buf: &mut [u8]) -> usize {
+        let mut builder = RtpBuilder::from_basic(buf, false, 111, Seq(1), Timestamp(960), 1111, [].into_iter())
+            .extension(ExtFormat::OneByte);
+        builder.write_ext(1, &[0x80 | 30]);
+        builder.write_ext(3, &[0x06, 0x00, 0x00]);
+        builder.write_ext(5, &[0x01, 0x02]);
+        builder.payload(&[1, 2, 3, 4], false)
+    }
+
+    #[test]
+    fn test_extmap() {
+        let map = ExtMap::from_sdp_lines(SDP.lines()).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.id(AbsSendTimeValue::URI), Some(3));
+        assert_eq!(map.uri(5), Some(TransportCcValue::URI));
+
+        assert!(matches!(ExtMap::new().insert_sdp_line("a=extmap:0 urn:x"), Err(RtpError::InvalidExtMap)));
+        assert!(matches!(ExtMap::new().insert_sdp_line("a=extmap:1"), Err(RtpError::InvalidExtMap)));
+
+        let mut other = ExtMap::new();
+        assert_eq!(other.insert(255, AbsSendTimeValue::URI).unwrap(), None);
+        assert_eq!(other.insert(255, TransportCcValue::URI).unwrap().as_deref(), Some(AbsSendTimeValue::URI));
+        assert!(matches!(other.insert(0, AudioLevelValue::URI), Err(RtpError::InvalidExtMap)));
+        assert!(matches!(other.insert(256, AudioLevelValue::URI), Err(RtpError::InvalidExtMap)));
+        assert_eq!(other.len(), 1);
+
+        let mut buf = vec![0_u8; 1500];
+        let len = build(&mut buf);
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+
+        let level = packet.ext_value::<AudioLevelValue>(&map).unwrap().unwrap();
+        assert!(level.voice);
+        assert_eq!(level.volume.0, 30);
+
+        let abs = packet.ext_value::<AbsSendTimeValue>(&map).unwrap().unwrap();
+        assert_eq!(abs.0, 0x06_0000);
+
+        let twcc = packet.ext_value::<TransportCcValue>(&map).unwrap().unwrap();
+        assert_eq!(twcc.seq, Seq(0x0102));
+
+        assert!(packet.ext_value::<AudioLevelValue>(&ExtMap::new()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_remap() {
+        let from = ExtMap::from_sdp_lines(SDP.lines()).unwrap();
+
+        let mut to = ExtMap::new();
+        to.insert(2, AudioLevelValue::URI).unwrap();
+        to.insert(7, TransportCcValue::URI).unwrap();
+
+        let mut buf = vec![0_u8; 1500];
+        let len = build(&mut buf);
+        ExtRemap::new(&from, &to).apply(&mut buf[..len]).unwrap();
+
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+        let exts: Vec<_> = packet.extension_iter().unwrap().collect();
+        assert_eq!(exts, [(2, &[0x80 | 30][..]), (7, &[0x01, 0x02][..])]);
+        assert_eq!(packet.payload(), &[1, 2, 3, 4]);
+
+        assert!(packet.ext_value::<AbsSendTimeValue>(&to).unwrap().is_none());
+        assert_eq!(packet.ext_value::<TransportCcValue>(&to).unwrap().unwrap().seq, Seq(0x0102));
+    }
+}
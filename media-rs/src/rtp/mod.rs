@@ -4,18 +4,46 @@ pub use rtp::*;
 mod rtcp;
 pub use rtcp::*;
 
+mod owned;
+pub use owned::*;
+
+mod ext_edit;
+pub use ext_edit::*;
+
 mod seq;
 pub use seq::*;
 
 mod timestamp;
 pub use timestamp::*;
 
+mod ntp;
+pub use ntp::*;
+
 pub mod extension;
 
+pub mod extmap;
+
 pub mod error;
 
 pub mod audio_level;
 
+pub mod abs_send_time;
+
+pub mod transport_cc;
+
+pub mod stream_id;
+
+pub mod dependency_descriptor;
+
+pub mod video_orientation;
+
+pub mod playout_delay;
+
+pub mod video_content_type;
+
+pub mod abs_capture_time;
+
+pub mod codecs;
 
 
 
@@ -0,0 +1,117 @@
+//! https://datatracker.ietf.org/doc/html/rfc5905#section-6
+//!
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+/// 64 bits NTP timestamp, 32.32 fixed point seconds since 1900-01-01
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NtpTime(pub u64);
+
+impl NtpTime {
+    /// Seconds from NTP epoch to UNIX epoch
+    pub const UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+    pub fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    #[inline]
+    pub fn seconds(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    #[inline]
+    pub fn fraction(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Middle 32 bits, as LSR of report block and RRTR
+    #[inline]
+    pub fn compact(&self) -> NtpCompact {
+        NtpCompact((self.0 >> 16) as u32)
+    }
+
+    /// Duration since NTP epoch, wraps every 2^32 seconds
+    pub fn from_duration(d: Duration) -> Self {
+        let fraction = ((d.subsec_nanos() as u128) << 32) / Self::NANOS_PER_SEC;
+        Self(d.as_secs() << 32 | fraction as u64)
+    }
+
+    /// Duration since NTP epoch
+    pub fn to_duration(&self) -> Duration {
+        let nanos = ((self.fraction() as u128) * Self::NANOS_PER_SEC) >> 32;
+        Duration::new(self.seconds() as u64, nanos as u32)
+    }
+
+    pub fn from_system_time(t: SystemTime) -> Self {
+        let since_unix = t.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self::from_duration(since_unix + Duration::from_secs(Self::UNIX_OFFSET_SECS))
+    }
+
+    /// Timestamps before UNIX epoch in seconds field are treated as era 1 (after 2036)
+    pub fn to_system_time(&self) -> SystemTime {
+        let mut d = self.to_duration();
+        if d.as_secs() < Self::UNIX_OFFSET_SECS {
+            d += Duration::from_secs(1 << 32);
+        }
+        UNIX_EPOCH + (d - Duration::from_secs(Self::UNIX_OFFSET_SECS))
+    }
+}
+
+impl From<u64> for NtpTime {
+    fn from(v: u64) -> Self {
+        Self(v)
+    }
+}
+
+
+/// Middle 32 bits of NTP timestamp, 16.16 fixed point seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NtpCompact(pub u32);
+
+impl NtpCompact {
+
+    pub fn from_duration(d: Duration) -> Self {
+        Self((NtpTime::from_duration(d).0 >> 16) as u32)
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        NtpTime((self.0 as u64) << 16).to_duration()
+    }
+
+    /// Elapsed time from rhs to self, e.g. round trip = now - LSR - DLSR
+    pub fn wrapping_sub(&self, rhs: Self) -> Duration {
+        Self(self.0.wrapping_sub(rhs.0)).to_duration()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{NtpCompact, NtpTime};
+
+    #[test]
+    fn test() {
+        let ntp = NtpTime::from_duration(Duration::from_millis(1500));
+        assert_eq!(ntp.0, 0x0000_0001_8000_0000);
+        assert_eq!(ntp.to_duration(), Duration::from_millis(1500));
+        assert_eq!(ntp.compact(), NtpCompact(0x0001_8000));
+        assert_eq!(ntp.compact().to_duration(), Duration::from_millis(1500));
+
+        let t = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let ntp = NtpTime::from_system_time(t);
+        assert_eq!(ntp.seconds() as u64, 1_700_000_000 + NtpTime::UNIX_OFFSET_SECS);
+        assert_eq!(ntp.to_system_time(), t);
+
+        // era 1
+        let t = UNIX_EPOCH + Duration::from_secs(2_100_000_000);
+        assert_eq!(NtpTime::from_system_time(t).to_system_time(), t);
+
+        assert_eq!(NtpCompact(0x0001_0000).wrapping_sub(NtpCompact(0xFFFF_8000)), Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+use bytes::Bytes;
+
+use super::{
+    error::RtpError, extmap::ExtMap, RefRtcpPacket, RefRtcpPackets, RefRtpPacket, RtcpKind, RtcpMode,
+};
+
+
+/// Owned RTP packet, validated once, cheap to clone and slice
+#[derive(Clone)]
+pub struct RtpPacket {
+    buf: Bytes,
+}
+
+impl TryFrom<Bytes> for RtpPacket {
+    type Error = RtpError;
+
+    fn try_from(buf: Bytes) -> Result<Self, Self::Error> {
+        RefRtpPacket::parse(&buf)?;
+        Ok(Self { buf })
+    }
+}
+
+impl RtpPacket {
+
+    #[inline]
+    pub fn as_ref(&self) -> RefRtpPacket<'_> {
+        RefRtpPacket::uncheck(&self.buf)
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &Bytes {
+        &self.buf
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Bytes {
+        self.buf
+    }
+
+    pub fn payload(&self) -> Bytes {
+        self.buf.slice_ref(self.as_ref().payload())
+    }
+
+    pub fn ext_by_id(&self, id: u8) -> Option<Bytes> {
+        self.as_ref().ext_by_id(id).map(|ext| self.buf.slice_ref(ext))
+    }
+
+    pub fn ext_by_uri(&self, map: &ExtMap, uri: &str) -> Option<Bytes> {
+        self.as_ref().ext_by_uri(map, uri).map(|ext| self.buf.slice_ref(ext))
+    }
+
+    pub fn extension_iter(&self) -> impl Iterator<Item = (u8, Bytes)> + '_ {
+        self.as_ref()
+            .extension_iter()
+            .into_iter()
+            .flatten()
+            .map(|(id, ext)| (id, self.buf.slice_ref(ext)))
+    }
+}
+
+impl fmt::Debug for RtpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Debug::fmt(&self.as_ref(), f)
+    }
+}
+
+impl fmt::Display for RtpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.as_ref(), f)
+    }
+}
+
+
+
+/// Owned single RTCP packet, validated once, cheap to clone and slice
+#[derive(Clone)]
+pub struct RtcpPacket {
+    buf: Bytes,
+}
+
+impl TryFrom<Bytes> for RtcpPacket {
+    type Error = RtpError;
+
+    /// The rest of buf after the first packet is discarded
+    fn try_from(buf: Bytes) -> Result<Self, Self::Error> {
+        let len = RefRtcpPacket::try_from(&buf[..])?.packet_len();
+        Ok(Self { buf: buf.slice(..len) })
+    }
+}
+
+impl RtcpPacket {
+
+    /// Split a compound packet after validating it in mode
+    pub fn split_compound(buf: Bytes, mode: RtcpMode) -> Result<Vec<Self>, RtpError> {
+        let packets = RefRtcpPackets::parse(&buf, mode)?;
+        let packets = packets.uncheck_iter()
+            .map(|packet| Self { buf: buf.slice_ref(packet.inner()) })
+            .collect();
+        Ok(packets)
+    }
+
+    #[inline]
+    pub fn as_ref(&self) -> RefRtcpPacket<'_> {
+        RefRtcpPacket::uncheck(&self.buf)
+    }
+
+    #[inline]
+    pub fn kind(&self) -> Result<RtcpKind<'_>, RtpError> {
+        self.as_ref().kind()
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &Bytes {
+        &self.buf
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Bytes {
+        self.buf
+    }
+
+    pub fn payload(&self) -> Bytes {
+        self.buf.slice_ref(self.as_ref().payload())
+    }
+}
+
+impl fmt::Debug for RtcpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Debug::fmt(&self.as_ref(), f)
+    }
+}
+
+impl fmt::Display for RtcpPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.as_ref(), f)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use bytes::Bytes;
+
+    use crate::rtp::{extension::ExtFormat, RtcpBuilder, RtcpKind, RtcpMode, RtpBuilder, Seq, Timestamp};
+
+    use super::{RtcpPacket, RtpPacket};
+
+    #[test]
+    fn test_owned_rtp() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtpBuilder::from_basic(&mut buf, true, 96, Seq(1), Timestamp(90), 1111, [].into_iter())
+            .extension(ExtFormat::OneByte);
+        builder.write_ext(1, &[0x01, 0x02]);
+        let len = builder.payload(&[1, 2, 3, 4], false);
+        buf.truncate(len);
+
+        let bytes = Bytes::from(buf);
+        let packet = RtpPacket::try_from(bytes.clone()).unwrap();
+        let payload = packet.payload();
+        assert_eq!(&payload[..], &[1, 2, 3, 4]);
+        // zero-copy
+        assert_eq!(payload.as_ptr(), packet.as_ref().payload().as_ptr());
+        assert!(bytes.as_ptr_range().contains(&payload.as_ptr()));
+        assert_eq!(packet.ext_by_id(1).as_deref(), Some(&[0x01, 0x02][..]));
+        assert_eq!(packet.extension_iter().count(), 1);
+
+        // still valid after dropping the original
+        let cloned = packet.clone();
+        drop(packet);
+        drop(bytes);
+        assert_eq!(cloned.as_ref().header().seq(), Seq(1));
+
+        assert!(RtpPacket::try_from(Bytes::from_static(&[0x80, 96, 0])).is_err());
+    }
+
+    #[test]
+    fn test_owned_rtcp() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.receiver_report(1111);
+        builder.pli(1111, 2222).unwrap();
+        let len = builder.finish().unwrap();
+        buf.truncate(len);
+
+        let packets = RtcpPacket::split_compound(Bytes::from(buf), RtcpMode::Compound).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(packets[0].kind(), Ok(RtcpKind::ReceiverReport(_))));
+        assert!(matches!(packets[1].kind(), Ok(RtcpKind::Psfb(_))));
+
+        let single = RtcpPacket::try_from(packets[0].inner().clone()).unwrap();
+        assert_eq!(single.inner().len(), 8);
+    }
+}
@@ -0,0 +1,109 @@
+//! http://www.webrtc.org/experiments/rtp-hdrext/playout-delay
+//!
+
+use std::time::Duration;
+
+use super::{error::RtpError, extmap::ExtValue};
+
+
+/*
+    0                   1                   2
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |       MIN delay       |       MAX delay       |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+/// Delays in units of 10 ms, 12 bits each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlayoutDelayValue {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl PlayoutDelayValue {
+    pub const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/playout-delay";
+
+    pub const LEN: usize = 3;
+
+    pub const UNIT_MS: u64 = 10;
+
+    pub const MAX_VALUE: u16 = 0x0FFF;
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.len() < Self::LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::LEN,
+                actual: data.len(),
+                origin: "Playout delay value length",
+            });
+        }
+
+        Ok(Self {
+            min: (data[0] as u16) << 4 | (data[1] as u16) >> 4,
+            max: ((data[1] & 0x0F) as u16) << 8 | data[2] as u16,
+        })
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 3] {
+        let min = self.min & Self::MAX_VALUE;
+        let max = self.max & Self::MAX_VALUE;
+        [
+            (min >> 4) as u8,
+            ((min & 0x0F) as u8) << 4 | (max >> 8) as u8,
+            max as u8,
+        ]
+    }
+
+    /// Rounded down to 10 ms, saturated at 40.95 s
+    pub fn from_durations(min: Duration, max: Duration) -> Self {
+        Self {
+            min: Self::units(min),
+            max: Self::units(max),
+        }
+    }
+
+    #[inline]
+    pub fn min_duration(&self) -> Duration {
+        Duration::from_millis(self.min as u64 * Self::UNIT_MS)
+    }
+
+    #[inline]
+    pub fn max_duration(&self) -> Duration {
+        Duration::from_millis(self.max as u64 * Self::UNIT_MS)
+    }
+
+    fn units(d: Duration) -> u16 {
+        let units = d.as_millis() / Self::UNIT_MS as u128;
+        units.min(Self::MAX_VALUE as u128) as u16
+    }
+}
+
+impl<'a> ExtValue<'a> for PlayoutDelayValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::PlayoutDelayValue;
+
+    #[test]
+    fn test() {
+        let v = PlayoutDelayValue::from_durations(Duration::from_millis(105), Duration::from_secs(60));
+        assert_eq!(v, PlayoutDelayValue { min: 10, max: 0x0FFF });
+        assert_eq!(v.to_bytes(), [0x00, 0xAF, 0xFF]);
+        assert_eq!(PlayoutDelayValue::parse(&v.to_bytes()).unwrap(), v);
+        assert_eq!(v.min_duration(), Duration::from_millis(100));
+        assert_eq!(v.max_duration(), Duration::from_millis(40950));
+
+        assert!(PlayoutDelayValue::parse(&[0, 0]).is_err());
+    }
+}
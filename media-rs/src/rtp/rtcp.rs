@@ -2,6 +2,71 @@ use std::fmt;
 
 use super::error::RtpError;
 
+mod report;
+pub use report::*;
+
+mod builder;
+pub use builder::*;
+
+mod sdes;
+pub use sdes::*;
+
+mod bye;
+pub use bye::*;
+
+mod feedback;
+pub use feedback::*;
+
+mod nack;
+pub use nack::*;
+
+mod psfb;
+pub use psfb::*;
+
+mod twcc;
+pub use twcc::*;
+
+mod remb;
+pub use remb::*;
+
+mod xr;
+pub use xr::*;
+
+mod app;
+pub use app::*;
+
+mod kind;
+pub use kind::*;
+
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RtcpType {
+    SenderReport = 200,
+    ReceiverReport = 201,
+    SourceDescription = 202,
+    Goodbye = 203,
+    App = 204,
+    TransportFeedback = 205,
+    PayloadFeedback = 206,
+    ExtendedReport = 207,
+}
+
+impl RtcpType {
+    pub fn from_num(num: u8) -> Option<Self> {
+        match num {
+            200 => Some(Self::SenderReport),
+            201 => Some(Self::ReceiverReport),
+            202 => Some(Self::SourceDescription),
+            203 => Some(Self::Goodbye),
+            204 => Some(Self::App),
+            205 => Some(Self::TransportFeedback),
+            206 => Some(Self::PayloadFeedback),
+            207 => Some(Self::ExtendedReport),
+            _ => None,
+        }
+    }
+}
 
 pub struct RefRtcpHeader<'a> {
     buf: &'a [u8],
@@ -78,6 +143,7 @@ impl<'a> TryFrom<&'a [u8]> for RefRtcpHeader<'a> {
 
 
 
+#[derive(Clone, Copy)]
 pub struct RefRtcpPacket<'a> {
     buf: &'a [u8],
 }
@@ -142,8 +208,18 @@ impl<'a> RefRtcpPacket<'a>  {
         &self.buf[self.payload_offset()..self.buf.len() - pad]
     }
 
+    /// Packet content after the first 4 bytes of header, padding excluded.
+    /// 
+    /// For SDES and BYE the SSRC field in header is actually the first chunk/source
+    pub fn body(&self) -> &'a [u8] {
+        
+        let pad = self.padding().unwrap_or(0) as usize;
+
+        &self.buf[4..self.buf.len() - pad]
+    }
+
     pub fn packet_len(&self) -> usize {
-        ((self.header().words_minus_one() + 1) * 4) as usize
+        (self.header().words_minus_one() as usize + 1) * 4
     }
 }
 
@@ -153,6 +229,25 @@ impl<'a> TryFrom<&'a [u8]> for RefRtcpPacket<'a> {
     fn try_from(buf: &'a [u8]) -> Result<Self, Self::Error> {
         let header = RefRtcpHeader::try_from(buf)?;
 
+        let packet_len = Self{ buf }.packet_len();
+        if buf.len() < packet_len {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: packet_len,
+                actual: buf.len(),
+                origin: "Rtcp packet length",
+            });
+        }
+
+        if packet_len < RefRtcpHeader::MIN_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: RefRtcpHeader::MIN_LEN,
+                actual: packet_len,
+                origin: "Rtcp packet length",
+            });
+        }
+
+        // the rest of buf belongs to the following packets of compound
+        let buf = &buf[..packet_len];
         let me = Self{ buf };
 
         let payload_offset = me.payload_offset();
@@ -178,15 +273,6 @@ impl<'a> TryFrom<&'a [u8]> for RefRtcpPacket<'a> {
             }
         }
 
-        let packet_len = me.packet_len();
-        if buf.len() < packet_len {
-            return Err(RtpError::NotEnoughBuffer {
-                expect: packet_len,
-                actual: buf.len(),
-                origin: "Rtcp packet length",
-            });
-        }
-
         Ok(me)
     }
 }
@@ -248,6 +334,56 @@ impl<'a> RefRtcpPackets<'a>  {
     }
 }
 
+impl<'a> RefRtcpPackets<'a> {
+
+    /// Validate all packets in buf according to mode
+    ///
+    /// In compound mode, checks of RFC 3550 Appendix A.2 are applied:
+    /// the first packet must be SR or RR, and only the last packet may have padding
+    pub fn parse(buf: &'a [u8], mode: RtcpMode) -> Result<Self, RtpError> {
+        if buf.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: RefRtcpHeader::MIN_LEN,
+                actual: 0,
+                origin: "Rtcp packets",
+            });
+        }
+
+        let mut iter = RtcpTryIter { buf }.peekable();
+        let mut is_first = true;
+
+        while let Some(packet) = iter.next() {
+            let packet = packet?;
+
+            if mode == RtcpMode::Compound {
+                let pt = packet.header().payload_type();
+                if is_first 
+                    && pt != RtcpType::SenderReport as u8 
+                    && pt != RtcpType::ReceiverReport as u8 {
+                    return Err(RtpError::CompoundFirstNotReport(pt));
+                }
+
+                if packet.header().padding_flag() && iter.peek().is_some() {
+                    return Err(RtpError::UnexpectedPadding);
+                }
+            }
+
+            is_first = false;
+        }
+
+        Ok(Self { buf })
+    }
+}
+
+/// Validation policy of [`RefRtcpPackets::parse`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RtcpMode {
+    /// RFC 3550, every datagram is a compound packet starting with SR/RR
+    Compound,
+    /// RFC 5506, negotiated by `a=rtcp-rsize`, any single packet is allowed
+    ReducedSize,
+}
+
 impl<'a> TryFrom<&'a [u8]> for RefRtcpPackets<'a> {
     type Error = RtpError;
 
@@ -294,8 +430,9 @@ impl<'a> Iterator for RtcpUncheckIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if !self.buf.is_empty() {
-            let item = RefRtcpPacket::uncheck(self.buf);
-            self.buf = &self.buf[item.packet_len()..];
+            let len = RefRtcpPacket::uncheck(self.buf).packet_len();
+            let item = RefRtcpPacket::uncheck(&self.buf[..len]);
+            self.buf = &self.buf[len..];
             Some(item)
         } else {
             None
@@ -353,3 +490,49 @@ impl<'a> fmt::Display for RefRtcpPackets<'a> {
 //     let pt = data[1] & 0x7F;
 //     return (63 < pt) && (pt < 96);
 // }
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::error::RtpError;
+
+    use super::{RefRtcpPackets, RtcpBuilder, RtcpMode};
+
+    #[test]
+    fn test_parse_mode() {
+        let mut buf = vec![0_u8; 1500];
+
+        // lone PLI
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.pli(1111, 2222).unwrap();
+        let len = builder.finish().unwrap();
+        assert!(RefRtcpPackets::parse(&buf[..len], RtcpMode::ReducedSize).is_ok());
+        assert!(matches!(
+            RefRtcpPackets::parse(&buf[..len], RtcpMode::Compound),
+            Err(RtpError::CompoundFirstNotReport(206))
+        ));
+
+        // RR + PLI, padding on the last
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.receiver_report(1111);
+        builder.pli(1111, 2222).unwrap();
+        let len = builder.finish_padding(4).unwrap();
+        assert!(RefRtcpPackets::parse(&buf[..len], RtcpMode::Compound).is_ok());
+        assert!(RefRtcpPackets::parse(&buf[..len], RtcpMode::ReducedSize).is_ok());
+
+        // padding on the first
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.receiver_report(1111);
+        let first_len = builder.finish_padding(4).unwrap();
+        let mut builder = RtcpBuilder::new(&mut buf[first_len..]);
+        builder.pli(1111, 2222).unwrap();
+        let len = first_len + builder.finish().unwrap();
+        assert!(matches!(
+            RefRtcpPackets::parse(&buf[..len], RtcpMode::Compound),
+            Err(RtpError::UnexpectedPadding)
+        ));
+        assert!(RefRtcpPackets::parse(&buf[..len], RtcpMode::ReducedSize).is_ok());
+
+        assert!(RefRtcpPackets::parse(&[], RtcpMode::ReducedSize).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! https://datatracker.ietf.org/doc/html/rfc3550#section-6.7
+//!
+
+use std::fmt;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefRtcpPacket, RtcpBuilder, RtcpType};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |V=2|P| subtype |   PT=APP=204  |             length            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                           SSRC/CSRC                           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                          name (ASCII)                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                   application-dependent data                ...
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+
+#[derive(Clone, Copy)]
+pub struct RefApp<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefApp<'a> {
+
+    pub const NAME_LEN: usize = 4;
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    #[inline]
+    pub fn subtype(&self) -> u8 {
+        self.packet.header().r_count()
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.packet.header().ssrc()
+    }
+
+    #[inline]
+    pub fn name(&self) -> [u8; 4] {
+        let payload = self.packet.payload();
+        [payload[0], payload[1], payload[2], payload[3]]
+    }
+
+    /// Application-dependent data, padding excluded
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        &self.packet.payload()[Self::NAME_LEN..]
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefApp<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let pt = packet.header().payload_type();
+        if pt != RtcpType::App as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        if packet.payload().len() < Self::NAME_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::NAME_LEN,
+                actual: packet.payload().len(),
+                origin: "Rtcp APP name",
+            });
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefApp<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("App")
+            .field("subtype", &self.subtype())
+            .field("ssrc", &self.ssrc())
+            .field("name", &String::from_utf8_lossy(&self.name()))
+            .field("data_length", &self.data().len())
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// data is padded with zero octets to 32 bits boundary
+    pub fn app(&mut self, subtype: u8, ssrc: u32, name: [u8; 4], data: &[u8]) -> Result<(), RtpError> {
+        self.packet(RtcpType::App as u8)
+        .set_count(subtype)?
+        .write_u32(ssrc)
+        .write_slice(&name)
+        .write_slice(data);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPackets, RtcpBuilder, RtcpKind};
+
+    #[test]
+    fn test_app_passthrough() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.receiver_report(1111);
+        builder.app(3, 1111, *b"TEST", &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        builder.packet(210).write_u32(1111).write_u32(0xAABB_CCDD);
+        let len = builder.finish().unwrap();
+
+        let packets = RefRtcpPackets::try_from(&buf[..len]).unwrap();
+        let kinds: Vec<_> = packets.uncheck_iter().map(|x| x.kind().unwrap()).collect();
+        assert_eq!(kinds.len(), 3);
+        assert!(matches!(kinds[0], RtcpKind::ReceiverReport(_)));
+
+        let RtcpKind::App(app) = kinds[1] else { panic!("expect app") };
+        assert_eq!(app.subtype(), 3);
+        assert_eq!(app.ssrc(), 1111);
+        assert_eq!(&app.name(), b"TEST");
+        assert_eq!(app.data(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let RtcpKind::Unknown(unknown) = kinds[2] else { panic!("expect unknown") };
+        assert_eq!(unknown.header().payload_type(), 210);
+        assert_eq!(unknown.payload(), &[0xAA, 0xBB, 0xCC, 0xDD]);
+
+        let mut out = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut out);
+        for kind in &kinds {
+            builder.write_packet(kind.packet()).unwrap();
+        }
+        let out_len = builder.finish().unwrap();
+        assert_eq!(&out[..out_len], &buf[..len]);
+    }
+}
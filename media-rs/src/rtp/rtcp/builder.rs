@@ -0,0 +1,322 @@
+use bytes::BufMut;
+
+use crate::rtp::error::RtpError;
+
+use super::RefRtcpPacket;
+
+
+/// Write compound RTCP packets into buf in place
+///
+/// Each packet starts with [`RtcpBuilder::packet`], it is aligned to 32 bits and its
+/// length and count are filled in when the returned [`RtcpPacketBuilder`] drops.
+pub struct RtcpBuilder<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    last: Option<usize>,
+    /// first error of a dropped packet builder, returned by finish
+    error: Option<RtpError>,
+}
+
+impl<'a> RtcpBuilder<'a> {
+
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            len: 0,
+            last: None,
+            error: None,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Begin a packet with only the first 4 bytes of common header,
+    /// the rest (SSRC etc.) is written by caller
+    pub fn packet<'b>(&'b mut self, payload_type: u8) -> RtcpPacketBuilder<'b, 'a> {
+        let offset = self.len;
+
+        {
+            let mut buf = &mut self.buf[offset..];
+            // version(2) = 2, padding(1), count(5)
+            buf.put_u8(0b1000_0000);
+            buf.put_u8(payload_type);
+            buf.put_u16(0); // words minus one
+        }
+
+        self.len += 4;
+        self.last = Some(offset);
+
+        RtcpPacketBuilder {
+            parent: self,
+            offset,
+            count: 0,
+        }
+    }
+
+    /// Copy a parsed packet byte-for-byte, padding stripped so that finish_padding can pad it again
+    ///
+    /// Error if the packet without padding is not multiple of 4.
+    pub fn write_packet(&mut self, packet: RefRtcpPacket<'_>) -> Result<(), RtpError> {
+        let src = packet.inner();
+        let padding = packet.padding().unwrap_or(0);
+        let len = src.len() - padding as usize;
+        if !len.is_multiple_of(4) {
+            return Err(RtpError::InvalidPaddingLength(padding));
+        }
+
+        let offset = self.len;
+        self.buf[offset..offset + len].copy_from_slice(&src[..len]);
+        self.len += len;
+
+        // buf[0]: version(2), padding(1), count(5)
+        self.buf[offset] &= 0b1101_1111;
+        write_words(self.buf, offset, len)?;
+
+        self.last = Some(offset);
+        Ok(())
+    }
+
+    /// Return total length of compound packet
+    pub fn finish(self) -> Result<usize, RtpError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.len),
+        }
+    }
+
+    /// Append padding to the last packet, return total length of compound packet
+    ///
+    /// padding_len includes the last octet and must be multiple of 4
+    pub fn finish_padding(mut self, padding_len: u8) -> Result<usize, RtpError> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        let last = match self.last {
+            Some(last) if padding_len > 0 && padding_len.is_multiple_of(4) => last,
+            _ => return Err(RtpError::InvalidPaddingLength(padding_len)),
+        };
+
+        {
+            let mut buf = &mut self.buf[self.len..];
+            buf.put_bytes(0, padding_len as usize - 1);
+            buf.put_u8(padding_len);
+        }
+        self.len += padding_len as usize;
+
+        // buf[0]: version(2), padding(1), count(5)
+        self.buf[last] |= 0b0010_0000;
+        write_words(self.buf, last, self.len - last)?;
+
+        Ok(self.len)
+    }
+}
+
+
+pub struct RtcpPacketBuilder<'a, 'b> {
+    parent: &'a mut RtcpBuilder<'b>,
+    offset: usize,
+    count: u8,
+}
+
+impl<'a, 'b> Drop for RtcpPacketBuilder<'a, 'b> {
+    fn drop(&mut self) {
+        self.align();
+
+        let len = self.parent.len - self.offset;
+        self.parent.buf[self.offset] |= self.count;
+        if let Err(e) = write_words(self.parent.buf, self.offset, len) {
+            self.parent.error.get_or_insert(e);
+        }
+    }
+}
+
+impl<'a, 'b> RtcpPacketBuilder<'a, 'b> {
+
+    pub const MAX_COUNT: u8 = 31;
+
+    /// Set the 5 bits count field, which is report count, source count, FMT or subtype
+    pub fn set_count(&mut self, count: u8) -> Result<&mut Self, RtpError> {
+        if count > Self::MAX_COUNT {
+            return Err(RtpError::InvalidRtcpCount(count));
+        }
+        self.count = count;
+        Ok(self)
+    }
+
+    pub fn inc_count(&mut self) -> Result<&mut Self, RtpError> {
+        self.set_count(self.count + 1)
+    }
+
+    pub fn write_u8(&mut self, value: u8) -> &mut Self {
+        self.tail_buf().put_u8(value);
+        self.parent.len += 1;
+        self
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> &mut Self {
+        self.tail_buf().put_u16(value);
+        self.parent.len += 2;
+        self
+    }
+
+    pub fn write_u32(&mut self, value: u32) -> &mut Self {
+        self.tail_buf().put_u32(value);
+        self.parent.len += 4;
+        self
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> &mut Self {
+        self.tail_buf().put_u64(value);
+        self.parent.len += 8;
+        self
+    }
+
+    pub fn write_slice(&mut self, value: &[u8]) -> &mut Self {
+        self.tail_buf().put_slice(value);
+        self.parent.len += value.len();
+        self
+    }
+
+    /// Write zeros up to 32 bits boundary
+    pub fn align(&mut self) -> &mut Self {
+        let len = self.parent.len - self.offset;
+        let padding_len = len.div_ceil(4) * 4 - len;
+        self.tail_buf().put_bytes(0, padding_len);
+        self.parent.len += padding_len;
+        self
+    }
+
+    fn tail_buf(&mut self) -> &mut [u8] {
+        let tail = self.parent.len;
+        &mut self.parent.buf[tail..]
+    }
+}
+
+/// Write length field, in 32 bits words minus one
+#[inline]
+fn write_words(buf: &mut [u8], offset: usize, len: usize) -> Result<(), RtpError> {
+    let words = (len / 4).checked_sub(1)
+        .and_then(|x| u16::try_from(x).ok())
+        .ok_or(RtpError::InvalidRtcpLength(len))?;
+    let mut buf = &mut buf[offset + 2..];
+    buf.put_u16(words);
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, RefReceiverReport, RefRtcpPacket, RefRtcpPackets, RefSenderReport, ReportBlock, SenderInfo, Timestamp};
+
+    use super::RtcpBuilder;
+
+    fn report_block(ssrc: u32) -> ReportBlock {
+        ReportBlock {
+            ssrc,
+            fraction_lost: 12,
+            cumulative_lost: -3,
+            ext_highest_seq: 0x0001_0002,
+            jitter: 33,
+            lsr: 0x1234_5678,
+            dlsr: 0x0002_0000,
+        }
+    }
+
+    #[test]
+    fn test_build_rtcp() {
+        let info = SenderInfo {
+            ntp_timestamp: 0x0102_0304_0506_0708,
+            rtp_timestamp: Timestamp(90000),
+            packet_count: 100,
+            octet_count: 12000,
+        };
+
+        for padding in [0_u8, 4, 8] {
+            let mut buf = vec![0_u8; 1500];
+
+            let mut builder = RtcpBuilder::new(&mut buf);
+
+            builder.sender_report(1111, &info)
+            .write_report(&report_block(2222)).unwrap()
+            .write_report(&report_block(3333)).unwrap();
+
+            builder.receiver_report(1111);
+
+            let len = if padding > 0 {
+                builder.finish_padding(padding).unwrap()
+            } else {
+                builder.finish().unwrap()
+            };
+            assert_eq!(len, 8 + 20 + 2 * 24 + 8 + padding as usize);
+
+            let packets = RefRtcpPackets::try_from(&buf[..len]).unwrap();
+            let mut iter = packets.uncheck_iter();
+
+            let sr = RefSenderReport::try_from(iter.next().unwrap()).unwrap();
+            assert_eq!(sr.packet().padding(), None);
+            assert_eq!(sr.ssrc(), 1111);
+            assert_eq!(sr.ntp_timestamp(), info.ntp_timestamp);
+            assert_eq!(sr.rtp_timestamp(), info.rtp_timestamp);
+            assert_eq!(sr.packet_count(), info.packet_count);
+            assert_eq!(sr.octet_count(), info.octet_count);
+            assert!(sr.report_iter().map(ReportBlock::from).eq([report_block(2222), report_block(3333)]));
+
+            let rr = RefReceiverReport::try_from(iter.next().unwrap()).unwrap();
+            assert_eq!(rr.packet().padding(), (padding > 0).then_some(padding));
+            assert_eq!(rr.ssrc(), 1111);
+            assert_eq!(rr.report_iter().count(), 0);
+            assert!(rr.profile_extension().is_empty());
+
+            assert!(iter.next().is_none());
+
+            // copy strips padding, then pad again
+            let mut out = vec![0_u8; 1500];
+            let mut builder = RtcpBuilder::new(&mut out);
+            for packet in packets.uncheck_iter() {
+                builder.write_packet(packet).unwrap();
+            }
+            assert_eq!(builder.finish_padding(4).unwrap(), 8 + 20 + 2 * 24 + 8 + 4);
+            let copied = RefRtcpPackets::try_from(&out[..len - padding as usize + 4]).unwrap();
+            assert_eq!(copied.uncheck_iter().last().unwrap().padding(), Some(4));
+        }
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut buf = vec![0_u8; 0x4_0010];
+
+        // count beyond 5 bits, unaligned packet padded on drop
+        let mut builder = RtcpBuilder::new(&mut buf);
+        {
+            let mut packet = builder.packet(204);
+            packet.set_count(31).unwrap();
+            assert!(matches!(packet.inc_count(), Err(RtpError::InvalidRtcpCount(32))));
+            assert!(matches!(packet.set_count(40), Err(RtpError::InvalidRtcpCount(40))));
+            packet.write_u32(1111).write_u8(1);
+        }
+        let len = builder.finish().unwrap();
+        assert_eq!(len, 12);
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        assert_eq!(packet.header().r_count(), 31);
+        assert_eq!(packet.header().words_minus_one(), 2);
+
+        // length beyond 16 bits words
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.packet(204).write_slice(&[0; 0x4_0000]);
+        assert!(matches!(builder.finish(), Err(RtpError::InvalidRtcpLength(0x4_0004))));
+
+        assert!(matches!(RtcpBuilder::new(&mut buf).finish_padding(4), Err(RtpError::InvalidPaddingLength(4))));
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.receiver_report(1111);
+        assert!(matches!(builder.finish_padding(3), Err(RtpError::InvalidPaddingLength(3))));
+    }
+}
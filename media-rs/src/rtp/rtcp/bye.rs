@@ -0,0 +1,161 @@
+//! https://datatracker.ietf.org/doc/html/rfc3550#section-6.6
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefRtcpPacket, RtcpBuilder, RtcpType};
+
+
+/*
+       0                   1                   2                   3
+       0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+      +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+      |V=2|P|    SC   |   PT=BYE=203  |             length            |
+      +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+      |                           SSRC/CSRC                           |
+      +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+      :                              ...                              :
+      +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+(opt) |     length    |               reason for leaving            ...
+      +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+
+#[derive(Clone, Copy)]
+pub struct RefBye<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefBye<'a> {
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    pub fn ssrc_iter(&self) -> impl Iterator<Item = u32> + 'a {
+        self.packet.body()
+            .chunks_exact(4)
+            .take(self.packet.header().r_count() as usize)
+            .map(|mut b| b.get_u32())
+    }
+
+    pub fn reason(&self) -> Option<&'a [u8]> {
+        let buf = &self.packet.body()[self.ssrcs_len()..];
+        if buf.is_empty() {
+            None
+        } else {
+            Some(&buf[1..1 + buf[0] as usize])
+        }
+    }
+
+    #[inline]
+    fn ssrcs_len(&self) -> usize {
+        4 * self.packet.header().r_count() as usize
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefBye<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let header = packet.header();
+        if header.payload_type() != RtcpType::Goodbye as u8 {
+            return Err(RtpError::UnexpectedRtcpType(header.payload_type()));
+        }
+
+        let me = Self { packet };
+        let body = packet.body();
+
+        let ssrcs_len = me.ssrcs_len();
+        if body.len() < ssrcs_len {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: ssrcs_len,
+                actual: body.len(),
+                origin: "Rtcp BYE ssrc list",
+            });
+        }
+
+        let buf = &body[ssrcs_len..];
+        if !buf.is_empty() {
+            let expect = 1 + buf[0] as usize;
+            if buf.len() < expect {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect,
+                    actual: buf.len(),
+                    origin: "Rtcp BYE reason",
+                });
+            }
+        }
+
+        Ok(me)
+    }
+}
+
+impl<'a> fmt::Debug for RefBye<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Bye")
+            .field("ssrcs", &self.ssrc_iter().collect::<Vec<_>>())
+            .field("reason", &self.reason().map(String::from_utf8_lossy))
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    pub fn bye<I>(&mut self, ssrc_iter: I, reason: Option<&[u8]>) -> Result<(), RtpError>
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        let mut builder = self.packet(RtcpType::Goodbye as u8);
+
+        for ssrc in ssrc_iter {
+            builder.inc_count()?.write_u32(ssrc);
+        }
+
+        if let Some(reason) = reason {
+            assert!(reason.len() < 256, "invalid RTCP BYE reason length [{}]", reason.len());
+            builder
+            .write_u8(reason.len() as u8)
+            .write_slice(reason)
+            .align();
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPackets, RtcpBuilder};
+
+    use super::RefBye;
+
+    #[test]
+    fn test_build_bye() {
+        let mut buf = vec![0_u8; 1500];
+
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.bye([1111, 2222], Some(b"bye")).unwrap();
+        builder.bye([3333], None).unwrap();
+        let len = builder.finish().unwrap();
+
+        let packets = RefRtcpPackets::try_from(&buf[..len]).unwrap();
+        let mut iter = packets.uncheck_iter();
+
+        let bye = RefBye::try_from(iter.next().unwrap()).unwrap();
+        assert!(bye.ssrc_iter().eq([1111, 2222]));
+        assert_eq!(bye.reason(), Some(&b"bye"[..]));
+
+        let bye = RefBye::try_from(iter.next().unwrap()).unwrap();
+        assert!(bye.ssrc_iter().eq([3333]));
+        assert_eq!(bye.reason(), None);
+
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,153 @@
+//! https://datatracker.ietf.org/doc/html/rfc4585#section-6.1
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefNack, RefRtcpPacket, RefTwcc, RtcpBuilder, RtcpPacketBuilder, RtcpType};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |V=2|P|   FMT   |       PT      |          length               |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                  SSRC of packet sender                        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                  SSRC of media source                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   :            Feedback Control Information (FCI)                 :
+   :                                                               :
+*/
+
+
+/// Common part of RTPFB(205) and PSFB(206)
+#[derive(Clone, Copy)]
+pub struct RefFeedback<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefFeedback<'a> {
+
+    pub const MEDIA_SSRC_LEN: usize = 4;
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    #[inline]
+    pub fn fmt(&self) -> u8 {
+        self.packet.header().r_count()
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.packet.header().ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        (&self.packet.payload()[0..]).get_u32()
+    }
+
+    #[inline]
+    pub fn fci(&self) -> &'a [u8] {
+        &self.packet.payload()[Self::MEDIA_SSRC_LEN..]
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefFeedback<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let pt = packet.header().payload_type();
+        if pt != RtcpType::TransportFeedback as u8 && pt != RtcpType::PayloadFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        if packet.payload().len() < Self::MEDIA_SSRC_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::MEDIA_SSRC_LEN,
+                actual: packet.payload().len(),
+                origin: "Rtcp feedback media ssrc",
+            });
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefFeedback<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Feedback")
+            .field("payload_type", &self.packet.header().payload_type())
+            .field("fmt", &self.fmt())
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .field("fci_length", &self.fci().len())
+            .finish()
+    }
+}
+
+
+
+/// Transport layer feedback dispatched on FMT
+#[derive(Debug, Clone, Copy)]
+pub enum RefRtpfb<'a> {
+    Nack(RefNack<'a>),
+    Twcc(RefTwcc<'a>),
+    Unknown(RefFeedback<'a>),
+}
+
+impl<'a> RefRtpfb<'a> {
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        match self {
+            Self::Nack(v) => v.feedback(),
+            Self::Twcc(v) => v.feedback(),
+            Self::Unknown(v) => *v,
+        }
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefRtpfb<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let pt = packet.header().payload_type();
+        if pt != RtcpType::TransportFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        let fb = RefFeedback::try_from(packet)?;
+        match fb.fmt() {
+            RefNack::FMT => Ok(Self::Nack(RefNack::try_from(fb)?)),
+            RefTwcc::FMT => Ok(Self::Twcc(RefTwcc::try_from(fb)?)),
+            _ => Ok(Self::Unknown(fb)),
+        }
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// Begin RTPFB/PSFB packet, FCI is written by caller
+    pub fn feedback<'b>(
+        &'b mut self,
+        rtcp_type: RtcpType,
+        fmt: u8,
+        sender_ssrc: u32,
+        media_ssrc: u32,
+    ) -> Result<RtcpPacketBuilder<'b, 'a>, RtpError> {
+        let mut builder = self.packet(rtcp_type as u8);
+        builder
+        .set_count(fmt)?
+        .write_u32(sender_ssrc)
+        .write_u32(media_ssrc);
+        Ok(builder)
+    }
+}
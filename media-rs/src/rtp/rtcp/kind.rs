@@ -0,0 +1,64 @@
+use crate::rtp::error::RtpError;
+
+use super::{
+    RefApp, RefBye, RefPsfb, RefReceiverReport, RefRtcpPacket, RefRtpfb,
+    RefSdes, RefSenderReport, RefXr, RtcpType,
+};
+
+
+/// Typed view of a single RTCP packet, dispatched on payload type
+#[derive(Debug, Clone, Copy)]
+pub enum RtcpKind<'a> {
+    SenderReport(RefSenderReport<'a>),
+    ReceiverReport(RefReceiverReport<'a>),
+    Sdes(RefSdes<'a>),
+    Bye(RefBye<'a>),
+    App(RefApp<'a>),
+    Rtpfb(RefRtpfb<'a>),
+    Psfb(RefPsfb<'a>),
+    Xr(RefXr<'a>),
+    /// Payload type without typed view, kept as is
+    Unknown(RefRtcpPacket<'a>),
+}
+
+impl<'a> RtcpKind<'a> {
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        match self {
+            Self::SenderReport(v) => v.packet(),
+            Self::ReceiverReport(v) => v.packet(),
+            Self::Sdes(v) => v.packet(),
+            Self::Bye(v) => v.packet(),
+            Self::App(v) => v.packet(),
+            Self::Rtpfb(v) => v.feedback().packet(),
+            Self::Psfb(v) => v.feedback().packet(),
+            Self::Xr(v) => v.packet(),
+            Self::Unknown(v) => *v,
+        }
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RtcpKind<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let kind = match RtcpType::from_num(packet.header().payload_type()) {
+            Some(RtcpType::SenderReport) => Self::SenderReport(packet.try_into()?),
+            Some(RtcpType::ReceiverReport) => Self::ReceiverReport(packet.try_into()?),
+            Some(RtcpType::SourceDescription) => Self::Sdes(packet.try_into()?),
+            Some(RtcpType::Goodbye) => Self::Bye(packet.try_into()?),
+            Some(RtcpType::App) => Self::App(packet.try_into()?),
+            Some(RtcpType::TransportFeedback) => Self::Rtpfb(packet.try_into()?),
+            Some(RtcpType::PayloadFeedback) => Self::Psfb(packet.try_into()?),
+            Some(RtcpType::ExtendedReport) => Self::Xr(packet.try_into()?),
+            None => Self::Unknown(packet),
+        };
+        Ok(kind)
+    }
+}
+
+impl<'a> RefRtcpPacket<'a> {
+    #[inline]
+    pub fn kind(&self) -> Result<RtcpKind<'a>, RtpError> {
+        RtcpKind::try_from(*self)
+    }
+}
@@ -0,0 +1,225 @@
+//! https://datatracker.ietf.org/doc/html/rfc4585#section-6.2.1
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::{error::RtpError, Seq};
+
+use super::{RefFeedback, RtcpBuilder, RtcpType};
+
+
+/*
+    Generic NACK FCI
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |            PID                |             BLP               |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    bit i of BLP set means packet PID+i+1 lost too
+*/
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NackPair {
+    pub pid: Seq,
+    pub blp: u16,
+}
+
+impl NackPair {
+
+    pub const LEN: usize = 4;
+
+    pub fn lost_iter(&self) -> impl Iterator<Item = Seq> {
+        let pid = self.pid;
+        let blp = self.blp;
+        std::iter::once(pid).chain(
+            (0..16_u16)
+            .filter(move |i| blp & (1 << i) != 0)
+            .map(move |i| pid + (i + 1))
+        )
+    }
+
+    /// Compress sequences into minimal list of pairs.
+    ///
+    /// Duplicated sequences are merged, wrap-around handled by `Seq` distance
+    pub fn compress<I>(seqs: I) -> Vec<Self>
+    where
+        I: IntoIterator<Item = Seq>,
+    {
+        let mut seqs: Vec<Seq> = seqs.into_iter().collect();
+        let Some(base) = seqs.first().copied() else {
+            return Vec::new();
+        };
+
+        // sort by distance to a fixed base so ordering is total
+        seqs.sort_by_key(|x| *x - base);
+        seqs.dedup();
+
+        let mut pairs: Vec<Self> = Vec::new();
+        for seq in seqs {
+            if let Some(last) = pairs.last_mut() {
+                let delta = seq - last.pid;
+                if delta > 0 && delta <= 16 {
+                    last.blp |= 1 << (delta - 1);
+                    continue;
+                }
+            }
+
+            pairs.push(Self {
+                pid: seq,
+                blp: 0,
+            });
+        }
+        pairs
+    }
+}
+
+
+#[derive(Clone, Copy)]
+pub struct RefNack<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefNack<'a> {
+
+    pub const FMT: u8 = 1;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        self.fb.media_ssrc()
+    }
+
+    pub fn pair_iter(&self) -> impl Iterator<Item = NackPair> + 'a {
+        self.fb.fci()
+            .chunks_exact(NackPair::LEN)
+            .map(|mut b| NackPair {
+                pid: Seq(b.get_u16()),
+                blp: b.get_u16(),
+            })
+    }
+
+    pub fn lost_iter(&self) -> impl Iterator<Item = Seq> + 'a {
+        self.pair_iter().flat_map(|x| x.lost_iter())
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefNack<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        let pt = fb.packet().header().payload_type();
+        if pt != RtcpType::TransportFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        if fb.fmt() != Self::FMT {
+            return Err(RtpError::UnexpectedFeedbackFmt(fb.fmt()));
+        }
+
+        let fci = fb.fci();
+        if fci.is_empty() || !fci.len().is_multiple_of(NackPair::LEN) {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: (fci.len() / NackPair::LEN + 1) * NackPair::LEN,
+                actual: fci.len(),
+                origin: "Rtcp NACK FCI",
+            });
+        }
+
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefNack<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Nack")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .field("lost", &self.lost_iter().map(|x| x.0).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    pub fn nack<I>(&mut self, sender_ssrc: u32, media_ssrc: u32, seqs: I) -> Result<(), RtpError>
+    where
+        I: IntoIterator<Item = Seq>,
+    {
+        let mut builder = self.feedback(RtcpType::TransportFeedback, RefNack::FMT, sender_ssrc, media_ssrc)?;
+
+        for pair in NackPair::compress(seqs) {
+            builder
+            .write_u16(pair.pid.0)
+            .write_u16(pair.blp);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPacket, RefRtpfb, RtcpBuilder, Seq};
+
+    use super::NackPair;
+
+    fn seqs(v: &[u16]) -> Vec<Seq> {
+        v.iter().map(|x| Seq(*x)).collect()
+    }
+
+    #[test]
+    fn test_compress() {
+        assert_eq!(NackPair::compress([]), vec![]);
+
+        assert_eq!(
+            NackPair::compress(seqs(&[5, 1, 2, 17, 18, 1])),
+            vec![
+                NackPair { pid: Seq(1), blp: 0b1000_0000_0000_1001 },
+                NackPair { pid: Seq(18), blp: 0 },
+            ],
+        );
+
+        // wrap around
+        assert_eq!(
+            NackPair::compress(seqs(&[1, 65534, 0, 65535])),
+            vec![
+                NackPair { pid: Seq(65534), blp: 0b0111 },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_build_nack() {
+        let lost = seqs(&[65530, 65535, 0, 3, 11, 12, 100]);
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.nack(1111, 2222, lost.iter().rev().copied()).unwrap();
+        let len = builder.finish().unwrap();
+
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        let RefRtpfb::Nack(nack) = RefRtpfb::try_from(packet).unwrap() else {
+            panic!("expect nack");
+        };
+
+        assert_eq!(nack.sender_ssrc(), 1111);
+        assert_eq!(nack.media_ssrc(), 2222);
+        assert_eq!(nack.pair_iter().count(), 3);
+        assert!(nack.lost_iter().eq(lost.iter().copied()));
+    }
+}
@@ -0,0 +1,504 @@
+//! https://datatracker.ietf.org/doc/html/rfc4585#section-6.3
+//! https://datatracker.ietf.org/doc/html/rfc5104#section-4.3.1
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefFeedback, RefRemb, RefRtcpPacket, RtcpBuilder, RtcpType};
+
+
+/// Payload specific feedback dispatched on FMT
+#[derive(Debug, Clone, Copy)]
+pub enum RefPsfb<'a> {
+    Pli(RefPli<'a>),
+    Sli(RefSli<'a>),
+    Rpsi(RefRpsi<'a>),
+    Fir(RefFir<'a>),
+    Remb(RefRemb<'a>),
+    /// Unknown FMT or application layer feedback other than REMB
+    Unknown(RefFeedback<'a>),
+}
+
+impl<'a> RefPsfb<'a> {
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        match self {
+            Self::Pli(v) => v.feedback(),
+            Self::Sli(v) => v.feedback(),
+            Self::Rpsi(v) => v.feedback(),
+            Self::Fir(v) => v.feedback(),
+            Self::Remb(v) => v.feedback(),
+            Self::Unknown(v) => *v,
+        }
+    }
+
+    /// PLI and FIR both ask sender for a key frame
+    pub fn is_keyframe_request(&self) -> bool {
+        matches!(self, Self::Pli(_) | Self::Fir(_))
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefPsfb<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let pt = packet.header().payload_type();
+        if pt != RtcpType::PayloadFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        let fb = RefFeedback::try_from(packet)?;
+        match fb.fmt() {
+            RefPli::FMT => Ok(Self::Pli(RefPli::try_from(fb)?)),
+            RefSli::FMT => Ok(Self::Sli(RefSli::try_from(fb)?)),
+            RefRpsi::FMT => Ok(Self::Rpsi(RefRpsi::try_from(fb)?)),
+            RefFir::FMT => Ok(Self::Fir(RefFir::try_from(fb)?)),
+            RefRemb::FMT => match RefRemb::try_from(fb) {
+                Ok(v) => Ok(Self::Remb(v)),
+                Err(RtpError::UnknownAfbIdentifier(_)) => Ok(Self::Unknown(fb)),
+                Err(e) => Err(e),
+            },
+            _ => Ok(Self::Unknown(fb)),
+        }
+    }
+}
+
+fn check_psfb(fb: &RefFeedback, fmt: u8) -> Result<(), RtpError> {
+    let pt = fb.packet().header().payload_type();
+    if pt != RtcpType::PayloadFeedback as u8 {
+        return Err(RtpError::UnexpectedRtcpType(pt));
+    }
+
+    if fb.fmt() != fmt {
+        return Err(RtpError::UnexpectedFeedbackFmt(fb.fmt()));
+    }
+
+    Ok(())
+}
+
+
+
+/// Picture Loss Indication, no FCI
+#[derive(Clone, Copy)]
+pub struct RefPli<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefPli<'a> {
+
+    pub const FMT: u8 = 1;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        self.fb.media_ssrc()
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefPli<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        check_psfb(&fb, Self::FMT)?;
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefPli<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Pli")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .finish()
+    }
+}
+
+
+
+/*
+    SLI FCI
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |            First        |        Number           | PictureID |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SliEntry {
+    /// 13 bits, address of the first lost macroblock
+    pub first: u16,
+    /// 13 bits, number of lost macroblocks
+    pub number: u16,
+    /// 6 bits
+    pub picture_id: u8,
+}
+
+impl SliEntry {
+    pub const LEN: usize = 4;
+
+    fn from_u32(v: u32) -> Self {
+        Self {
+            first: (v >> 19) as u16,
+            number: ((v >> 6) & 0x1FFF) as u16,
+            picture_id: (v & 0x3F) as u8,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        (self.first as u32 & 0x1FFF) << 19
+        | (self.number as u32 & 0x1FFF) << 6
+        | (self.picture_id as u32 & 0x3F)
+    }
+}
+
+/// Slice Loss Indication
+#[derive(Clone, Copy)]
+pub struct RefSli<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefSli<'a> {
+
+    pub const FMT: u8 = 2;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        self.fb.media_ssrc()
+    }
+
+    pub fn entry_iter(&self) -> impl Iterator<Item = SliEntry> + 'a {
+        self.fb.fci()
+            .chunks_exact(SliEntry::LEN)
+            .map(|mut b| SliEntry::from_u32(b.get_u32()))
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefSli<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        check_psfb(&fb, Self::FMT)?;
+
+        let fci = fb.fci();
+        if fci.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: SliEntry::LEN,
+                actual: fci.len(),
+                origin: "Rtcp SLI FCI",
+            });
+        }
+
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefSli<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Sli")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .field("entries", &self.entry_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+/*
+    RPSI FCI
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |      PB       |0| Payload Type|    Native RPSI bit string     |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |   defined per codec          ...                | Padding (0) |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+/// Reference Picture Selection Indication
+#[derive(Clone, Copy)]
+pub struct RefRpsi<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefRpsi<'a> {
+
+    pub const FMT: u8 = 3;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        self.fb.media_ssrc()
+    }
+
+    /// Number of padding bits at the end of FCI
+    #[inline]
+    pub fn padding_bits(&self) -> u8 {
+        self.fb.fci()[0]
+    }
+
+    #[inline]
+    pub fn payload_type(&self) -> u8 {
+        self.fb.fci()[1] & 0x7F
+    }
+
+    /// Native RPSI bit string, the whole padding octets excluded
+    pub fn bit_string(&self) -> &'a [u8] {
+        let fci = self.fb.fci();
+        &fci[2..fci.len() - self.padding_bits() as usize / 8]
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefRpsi<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        check_psfb(&fb, Self::FMT)?;
+
+        let fci = fb.fci();
+        if fci.len() < 2 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 2,
+                actual: fci.len(),
+                origin: "Rtcp RPSI header",
+            });
+        }
+
+        let expect = 2 + (fci[0] as usize).div_ceil(8);
+        if fci.len() < expect {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: fci.len(),
+                origin: "Rtcp RPSI padding bits",
+            });
+        }
+
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefRpsi<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Rpsi")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .field("payload_type", &self.payload_type())
+            .field("padding_bits", &self.padding_bits())
+            .field("bit_string_length", &self.bit_string().len())
+            .finish()
+    }
+}
+
+
+
+/*
+    FIR FCI
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                              SSRC                             |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   | Seq nr.       |    Reserved                                   |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    "SSRC of media source" in common header is not used and SHALL be 0
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FirEntry {
+    pub ssrc: u32,
+    /// command sequence number, increased by 1 for each new request
+    pub seq_nr: u8,
+}
+
+impl FirEntry {
+    pub const LEN: usize = 8;
+}
+
+/// Full Intra Request
+#[derive(Clone, Copy)]
+pub struct RefFir<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefFir<'a> {
+
+    pub const FMT: u8 = 4;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    pub fn entry_iter(&self) -> impl Iterator<Item = FirEntry> + 'a {
+        self.fb.fci()
+            .chunks_exact(FirEntry::LEN)
+            .map(|mut b| FirEntry {
+                ssrc: b.get_u32(),
+                seq_nr: b.get_u8(),
+            })
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefFir<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        check_psfb(&fb, Self::FMT)?;
+
+        let fci = fb.fci();
+        if fci.is_empty() || !fci.len().is_multiple_of(FirEntry::LEN) {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: (fci.len() / FirEntry::LEN + 1) * FirEntry::LEN,
+                actual: fci.len(),
+                origin: "Rtcp FIR FCI",
+            });
+        }
+
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefFir<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Fir")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("entries", &self.entry_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    pub fn pli(&mut self, sender_ssrc: u32, media_ssrc: u32) -> Result<(), RtpError> {
+        self.feedback(RtcpType::PayloadFeedback, RefPli::FMT, sender_ssrc, media_ssrc)?;
+        Ok(())
+    }
+
+    pub fn sli<I>(&mut self, sender_ssrc: u32, media_ssrc: u32, entries: I) -> Result<(), RtpError>
+    where
+        I: IntoIterator<Item = SliEntry>,
+    {
+        let mut builder = self.feedback(RtcpType::PayloadFeedback, RefSli::FMT, sender_ssrc, media_ssrc)?;
+        for entry in entries {
+            builder.write_u32(entry.to_u32());
+        }
+        Ok(())
+    }
+
+    /// bit_string is padded with zero octets to 32 bits boundary
+    pub fn rpsi(&mut self, sender_ssrc: u32, media_ssrc: u32, payload_type: u8, bit_string: &[u8]) -> Result<(), RtpError> {
+        let len = 2 + bit_string.len();
+        let padding_len = len.div_ceil(4) * 4 - len;
+
+        let mut builder = self.feedback(RtcpType::PayloadFeedback, RefRpsi::FMT, sender_ssrc, media_ssrc)?;
+        builder
+        .write_u8((padding_len * 8) as u8)
+        .write_u8(payload_type & 0x7F)
+        .write_slice(bit_string)
+        .align();
+        Ok(())
+    }
+
+    pub fn fir<I>(&mut self, sender_ssrc: u32, entries: I) -> Result<(), RtpError>
+    where
+        I: IntoIterator<Item = FirEntry>,
+    {
+        let mut builder = self.feedback(RtcpType::PayloadFeedback, RefFir::FMT, sender_ssrc, 0)?;
+        for entry in entries {
+            builder
+            .write_u32(entry.ssrc)
+            .write_u8(entry.seq_nr)
+            .write_slice(&[0; 3]);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPackets, RtcpBuilder};
+
+    use super::{FirEntry, RefPsfb, SliEntry};
+
+    #[test]
+    fn test_build_psfb() {
+        let sli = SliEntry { first: 0x1ABC, number: 0x0123, picture_id: 0x2A };
+        let firs = [
+            FirEntry { ssrc: 3333, seq_nr: 7 },
+            FirEntry { ssrc: 4444, seq_nr: 255 },
+        ];
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.pli(1111, 2222).unwrap();
+        builder.sli(1111, 2222, [sli]).unwrap();
+        builder.rpsi(1111, 2222, 96, &[1, 2, 3]).unwrap();
+        builder.fir(1111, firs).unwrap();
+        let len = builder.finish().unwrap();
+
+        let packets = RefRtcpPackets::try_from(&buf[..len]).unwrap();
+        let mut iter = packets.uncheck_iter().map(|x| RefPsfb::try_from(x).unwrap());
+
+        let RefPsfb::Pli(pli) = iter.next().unwrap() else { panic!() };
+        assert_eq!(pli.sender_ssrc(), 1111);
+        assert_eq!(pli.media_ssrc(), 2222);
+
+        let RefPsfb::Sli(v) = iter.next().unwrap() else { panic!() };
+        assert!(v.entry_iter().eq([sli]));
+
+        let RefPsfb::Rpsi(v) = iter.next().unwrap() else { panic!() };
+        assert_eq!(v.payload_type(), 96);
+        assert_eq!(v.padding_bits(), 24);
+        assert_eq!(v.bit_string(), &[1, 2, 3]);
+
+        let RefPsfb::Fir(v) = iter.next().unwrap() else { panic!() };
+        assert!(RefPsfb::Fir(v).is_keyframe_request());
+        assert_eq!(v.feedback().media_ssrc(), 0);
+        assert!(v.entry_iter().eq(firs));
+
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,223 @@
+//! https://datatracker.ietf.org/doc/html/draft-alvestrand-rmcat-remb-03#section-2.2
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefFeedback, RtcpBuilder, RtcpType};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |V=2|P| FMT=15  |   PT=206      |             length            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                  SSRC of packet sender                        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                  SSRC of media source                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |  Unique identifier 'R' 'E' 'M' 'B'                            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |  Num SSRC     | BR Exp    |  BR Mantissa                      |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |   SSRC feedback                                               |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |  ...                                                          |
+
+    bitrate = mantissa * 2^exp bps, "SSRC of media source" is always 0
+*/
+
+
+#[derive(Clone, Copy)]
+pub struct RefRemb<'a> {
+    fb: RefFeedback<'a>,
+}
+
+impl<'a> RefRemb<'a> {
+
+    /// Application layer feedback
+    pub const FMT: u8 = 15;
+
+    pub const IDENTIFIER: [u8; 4] = *b"REMB";
+
+    pub const FIXED_LEN: usize = 8;
+
+    const MANTISSA_BITS: u32 = 18;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn num_ssrc(&self) -> u8 {
+        self.fb.fci()[4]
+    }
+
+    #[inline]
+    pub fn exponent(&self) -> u8 {
+        self.fb.fci()[5] >> 2
+    }
+
+    #[inline]
+    pub fn mantissa(&self) -> u32 {
+        let fci = self.fb.fci();
+        u32::from_be_bytes([0, fci[5] & 0x03, fci[6], fci[7]])
+    }
+
+    /// bits per second, saturated at u64::MAX
+    pub fn bitrate(&self) -> u64 {
+        let mantissa = self.mantissa() as u64;
+        let exp = self.exponent() as u32;
+        if mantissa.leading_zeros() < exp {
+            u64::MAX
+        } else {
+            mantissa << exp
+        }
+    }
+
+    pub fn ssrc_iter(&self) -> impl Iterator<Item = u32> + 'a {
+        self.fb.fci()[Self::FIXED_LEN..]
+            .chunks_exact(4)
+            .take(self.num_ssrc() as usize)
+            .map(|mut b| b.get_u32())
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefRemb<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        let pt = fb.packet().header().payload_type();
+        if pt != RtcpType::PayloadFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        if fb.fmt() != Self::FMT {
+            return Err(RtpError::UnexpectedFeedbackFmt(fb.fmt()));
+        }
+
+        let fci = fb.fci();
+        if fci.len() < 4 {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 4,
+                actual: fci.len(),
+                origin: "Rtcp AFB identifier",
+            });
+        }
+
+        let identifier = [fci[0], fci[1], fci[2], fci[3]];
+        if identifier != Self::IDENTIFIER {
+            return Err(RtpError::UnknownAfbIdentifier(identifier));
+        }
+
+        if fci.len() < Self::FIXED_LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::FIXED_LEN,
+                actual: fci.len(),
+                origin: "Rtcp REMB bitrate",
+            });
+        }
+
+        let expect = Self::FIXED_LEN + 4 * fci[4] as usize;
+        if fci.len() < expect {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: fci.len(),
+                origin: "Rtcp REMB ssrc list",
+            });
+        }
+
+        Ok(Self { fb })
+    }
+}
+
+impl<'a> fmt::Debug for RefRemb<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Remb")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("bitrate", &self.bitrate())
+            .field("ssrcs", &self.ssrc_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// bitrate is rounded down to 18 bits precision
+    pub fn remb(&mut self, sender_ssrc: u32, bitrate: u64, ssrcs: &[u32]) -> Result<(), RtpError> {
+        assert!(ssrcs.len() < 256, "too many REMB ssrc [{}]", ssrcs.len());
+
+        let bits = u64::BITS - bitrate.leading_zeros();
+        let exp = bits.saturating_sub(RefRemb::MANTISSA_BITS);
+        let mantissa = (bitrate >> exp) as u32;
+
+        let mut builder = self.feedback(RtcpType::PayloadFeedback, RefRemb::FMT, sender_ssrc, 0)?;
+        builder
+        .write_slice(&RefRemb::IDENTIFIER)
+        .write_u32((ssrcs.len() as u32) << 24 | exp << 18 | mantissa);
+
+        for ssrc in ssrcs {
+            builder.write_u32(*ssrc);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, RefFeedback, RefPsfb, RefRtcpPacket, RtcpBuilder, RtcpType};
+
+    use super::RefRemb;
+
+    fn build_and_parse(bitrate: u64, ssrcs: &[u32], buf: &mut [u8]) -> u64 {
+        let mut builder = RtcpBuilder::new(buf);
+        builder.remb(1111, bitrate, ssrcs).unwrap();
+        let len = builder.finish().unwrap();
+
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        let RefPsfb::Remb(remb) = RefPsfb::try_from(packet).unwrap() else {
+            panic!("expect remb");
+        };
+        assert_eq!(remb.sender_ssrc(), 1111);
+        assert_eq!(remb.feedback().media_ssrc(), 0);
+        assert!(remb.ssrc_iter().eq(ssrcs.iter().copied()));
+        remb.bitrate()
+    }
+
+    #[test]
+    fn test_build_remb() {
+        let mut buf = vec![0_u8; 1500];
+
+        assert_eq!(build_and_parse(0, &[], &mut buf), 0);
+        assert_eq!(build_and_parse(0x3FFFF, &[2222], &mut buf), 0x3FFFF);
+        assert_eq!(build_and_parse(1_000_003, &[2222, 3333], &mut buf), 1_000_000);
+        assert_eq!(build_and_parse(u64::MAX, &[2222], &mut buf), 0x3FFFF << 46);
+    }
+
+    #[test]
+    fn test_not_remb() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.feedback(RtcpType::PayloadFeedback, RefRemb::FMT, 1111, 0).unwrap()
+        .write_slice(b"ABCD")
+        .write_u32(0);
+        let len = builder.finish().unwrap();
+
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        let fb = RefFeedback::try_from(packet).unwrap();
+        assert!(matches!(RefRemb::try_from(fb), Err(RtpError::UnknownAfbIdentifier(id)) if &id == b"ABCD"));
+        assert!(matches!(RefPsfb::try_from(packet), Ok(RefPsfb::Unknown(_))));
+    }
+}
@@ -0,0 +1,447 @@
+//! https://datatracker.ietf.org/doc/html/rfc3550#section-6.4
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::{error::RtpError, NtpTime, Timestamp};
+
+use super::{RefRtcpPacket, RtcpBuilder, RtcpPacketBuilder, RtcpType};
+
+
+/*
+    Sender Report
+
+        0                   1                   2                   3
+        0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+header |V=2|P|    RC   |   PT=SR=200   |             length            |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                         SSRC of sender                        |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+sender |              NTP timestamp, most significant word             |
+info   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |             NTP timestamp, least significant word             |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                         RTP timestamp                         |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                     sender's packet count                     |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                      sender's octet count                     |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+report |                 SSRC_1 (SSRC of first source)                 |
+block  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+  1    | fraction lost |       cumulative number of packets lost       |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |           extended highest sequence number received           |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                      interarrival jitter                      |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                         last SR (LSR)                         |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                   delay since last SR (DLSR)                  |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+       |                  profile-specific extensions                  |
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    Receiver Report is the same without sender info, PT=RR=201
+*/
+
+
+#[derive(Clone, Copy)]
+pub struct RefSenderReport<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefSenderReport<'a> {
+
+    pub const SENDER_INFO_LEN: usize = 20;
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.packet.header().ssrc()
+    }
+
+    /// 64 bits NTP timestamp, seconds in the high 32 bits
+    #[inline]
+    pub fn ntp_timestamp(&self) -> u64 {
+        (&self.packet.payload()[0..]).get_u64()
+    }
+
+    #[inline]
+    pub fn ntp_time(&self) -> NtpTime {
+        NtpTime(self.ntp_timestamp())
+    }
+
+    #[inline]
+    pub fn rtp_timestamp(&self) -> Timestamp {
+        Timestamp((&self.packet.payload()[8..]).get_u32())
+    }
+
+    #[inline]
+    pub fn packet_count(&self) -> u32 {
+        (&self.packet.payload()[12..]).get_u32()
+    }
+
+    #[inline]
+    pub fn octet_count(&self) -> u32 {
+        (&self.packet.payload()[16..]).get_u32()
+    }
+
+    pub fn report_iter(&self) -> impl Iterator<Item = RefReportBlock<'a>> + 'a {
+        report_iter(&self.packet.payload()[Self::SENDER_INFO_LEN..], self.packet.header().r_count())
+    }
+
+    pub fn profile_extension(&self) -> &'a [u8] {
+        let offset = Self::SENDER_INFO_LEN + reports_len(self.packet.header().r_count());
+        &self.packet.payload()[offset..]
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefSenderReport<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let header = packet.header();
+        if header.payload_type() != RtcpType::SenderReport as u8 {
+            return Err(RtpError::UnexpectedRtcpType(header.payload_type()));
+        }
+
+        let expect = Self::SENDER_INFO_LEN + reports_len(header.r_count());
+        if packet.payload().len() < expect {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: packet.payload().len(),
+                origin: "Rtcp SR report blocks",
+            });
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefSenderReport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("SenderReport")
+            .field("ssrc", &self.ssrc())
+            .field("ntp", &self.ntp_timestamp())
+            .field("rtp_timestamp", &self.rtp_timestamp())
+            .field("packet_count", &self.packet_count())
+            .field("octet_count", &self.octet_count())
+            .field("reports", &self.report_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+#[derive(Clone, Copy)]
+pub struct RefReceiverReport<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefReceiverReport<'a> {
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.packet.header().ssrc()
+    }
+
+    pub fn report_iter(&self) -> impl Iterator<Item = RefReportBlock<'a>> + 'a {
+        report_iter(self.packet.payload(), self.packet.header().r_count())
+    }
+
+    pub fn profile_extension(&self) -> &'a [u8] {
+        let offset = reports_len(self.packet.header().r_count());
+        &self.packet.payload()[offset..]
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefReceiverReport<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let header = packet.header();
+        if header.payload_type() != RtcpType::ReceiverReport as u8 {
+            return Err(RtpError::UnexpectedRtcpType(header.payload_type()));
+        }
+
+        let expect = reports_len(header.r_count());
+        if packet.payload().len() < expect {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: packet.payload().len(),
+                origin: "Rtcp RR report blocks",
+            });
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefReceiverReport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("ReceiverReport")
+            .field("ssrc", &self.ssrc())
+            .field("reports", &self.report_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+#[derive(Clone, Copy)]
+pub struct RefReportBlock<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> RefReportBlock<'a> {
+
+    pub const LEN: usize = 24;
+
+    pub fn new(buf: &'a [u8]) -> Self {
+        assert!(buf.len() >= Self::LEN);
+        Self { buf }
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        (&self.buf[0..]).get_u32()
+    }
+
+    #[inline]
+    pub fn fraction_lost(&self) -> u8 {
+        self.buf[4]
+    }
+
+    /// signed 24 bits, may be negative if duplicated packets received
+    #[inline]
+    pub fn cumulative_lost(&self) -> i32 {
+        let raw = u32::from_be_bytes([self.buf[5], self.buf[6], self.buf[7], 0]);
+        (raw as i32) >> 8
+    }
+
+    #[inline]
+    pub fn ext_highest_seq(&self) -> u32 {
+        (&self.buf[8..]).get_u32()
+    }
+
+    #[inline]
+    pub fn jitter(&self) -> u32 {
+        (&self.buf[12..]).get_u32()
+    }
+
+    #[inline]
+    pub fn lsr(&self) -> u32 {
+        (&self.buf[16..]).get_u32()
+    }
+
+    #[inline]
+    pub fn dlsr(&self) -> u32 {
+        (&self.buf[20..]).get_u32()
+    }
+}
+
+impl<'a> fmt::Debug for RefReportBlock<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("ReportBlock")
+            .field("ssrc", &self.ssrc())
+            .field("fraction_lost", &self.fraction_lost())
+            .field("cumulative_lost", &self.cumulative_lost())
+            .field("ext_highest_seq", &self.ext_highest_seq())
+            .field("jitter", &self.jitter())
+            .field("lsr", &self.lsr())
+            .field("dlsr", &self.dlsr())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SenderInfo {
+    pub ntp_timestamp: u64,
+    pub rtp_timestamp: Timestamp,
+    pub packet_count: u32,
+    pub octet_count: u32,
+}
+
+impl<'a> From<RefSenderReport<'a>> for SenderInfo {
+    fn from(sr: RefSenderReport<'a>) -> Self {
+        Self {
+            ntp_timestamp: sr.ntp_timestamp(),
+            rtp_timestamp: sr.rtp_timestamp(),
+            packet_count: sr.packet_count(),
+            octet_count: sr.octet_count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReportBlock {
+    pub ssrc: u32,
+    pub fraction_lost: u8,
+    pub cumulative_lost: i32,
+    pub ext_highest_seq: u32,
+    pub jitter: u32,
+    pub lsr: u32,
+    pub dlsr: u32,
+}
+
+impl<'a> From<RefReportBlock<'a>> for ReportBlock {
+    fn from(block: RefReportBlock<'a>) -> Self {
+        Self {
+            ssrc: block.ssrc(),
+            fraction_lost: block.fraction_lost(),
+            cumulative_lost: block.cumulative_lost(),
+            ext_highest_seq: block.ext_highest_seq(),
+            jitter: block.jitter(),
+            lsr: block.lsr(),
+            dlsr: block.dlsr(),
+        }
+    }
+}
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    pub fn sender_report<'b>(&'b mut self, ssrc: u32, info: &SenderInfo) -> RtcpPacketBuilder<'b, 'a> {
+        let mut builder = self.packet(RtcpType::SenderReport as u8);
+        builder
+        .write_u32(ssrc)
+        .write_u64(info.ntp_timestamp)
+        .write_u32(info.rtp_timestamp.0)
+        .write_u32(info.packet_count)
+        .write_u32(info.octet_count);
+        builder
+    }
+
+    pub fn receiver_report<'b>(&'b mut self, ssrc: u32) -> RtcpPacketBuilder<'b, 'a> {
+        let mut builder = self.packet(RtcpType::ReceiverReport as u8);
+        builder.write_u32(ssrc);
+        builder
+    }
+}
+
+impl<'a, 'b> RtcpPacketBuilder<'a, 'b> {
+
+    /// Append a report block to SR/RR and increase report count
+    pub fn write_report(&mut self, block: &ReportBlock) -> Result<&mut Self, RtpError> {
+        // cumulative lost is signed 24 bits
+        let lost = (block.cumulative_lost.clamp(-0x80_0000, 0x7F_FFFF) as u32) & 0x00FF_FFFF;
+
+        Ok(self
+        .inc_count()?
+        .write_u32(block.ssrc)
+        .write_u32((block.fraction_lost as u32) << 24 | lost)
+        .write_u32(block.ext_highest_seq)
+        .write_u32(block.jitter)
+        .write_u32(block.lsr)
+        .write_u32(block.dlsr))
+    }
+}
+
+#[inline]
+fn reports_len(r_count: u8) -> usize {
+    RefReportBlock::LEN * r_count as usize
+}
+
+fn report_iter(buf: &[u8], r_count: u8) -> impl Iterator<Item = RefReportBlock<'_>> + '_ {
+    buf.chunks_exact(RefReportBlock::LEN)
+        .take(r_count as usize)
+        .map(RefReportBlock::new)
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, RefRtcpPacket, RefRtcpPackets, Timestamp};
+
+    use super::{RefReceiverReport, RefSenderReport};
+
+    fn report_block(ssrc: u32, cumulative_lost: [u8; 3]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend_from_slice(&ssrc.to_be_bytes());
+        buf.push(64);
+        buf.extend_from_slice(&cumulative_lost);
+        buf.extend_from_slice(&1000_u32.to_be_bytes());
+        buf.extend_from_slice(&20_u32.to_be_bytes());
+        buf.extend_from_slice(&0x1234_5678_u32.to_be_bytes());
+        buf.extend_from_slice(&65536_u32.to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn test_parse_sr_rr() {
+        let mut buf = vec![0x81, 200, 0, 12];
+        buf.extend_from_slice(&1111_u32.to_be_bytes());
+        buf.extend_from_slice(&0x0102_0304_0506_0708_u64.to_be_bytes());
+        buf.extend_from_slice(&3333_u32.to_be_bytes());
+        buf.extend_from_slice(&44_u32.to_be_bytes());
+        buf.extend_from_slice(&5555_u32.to_be_bytes());
+        buf.extend(report_block(2222, [0, 0, 3]));
+
+        buf.extend_from_slice(&[0x82, 201, 0, 13]);
+        buf.extend_from_slice(&1111_u32.to_be_bytes());
+        buf.extend(report_block(2222, [0xFF, 0xFF, 0xFF]));
+        buf.extend(report_block(3333, [0, 1, 0]));
+
+        let packets = RefRtcpPackets::try_from(&buf[..]).unwrap();
+        let mut iter = packets.uncheck_iter();
+
+        let sr = RefSenderReport::try_from(iter.next().unwrap()).unwrap();
+        assert_eq!(sr.ssrc(), 1111);
+        assert_eq!(sr.ntp_timestamp(), 0x0102_0304_0506_0708);
+        assert_eq!(sr.rtp_timestamp(), Timestamp(3333));
+        assert_eq!(sr.packet_count(), 44);
+        assert_eq!(sr.octet_count(), 5555);
+        assert!(sr.profile_extension().is_empty());
+
+        let blocks: Vec<_> = sr.report_iter().collect();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].ssrc(), 2222);
+        assert_eq!(blocks[0].fraction_lost(), 64);
+        assert_eq!(blocks[0].cumulative_lost(), 3);
+        assert_eq!(blocks[0].ext_highest_seq(), 1000);
+        assert_eq!(blocks[0].jitter(), 20);
+        assert_eq!(blocks[0].lsr(), 0x1234_5678);
+        assert_eq!(blocks[0].dlsr(), 65536);
+
+        let rr = RefReceiverReport::try_from(iter.next().unwrap()).unwrap();
+        assert_eq!(rr.ssrc(), 1111);
+        let blocks: Vec<_> = rr.report_iter().collect();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].cumulative_lost(), -1);
+        assert_eq!(blocks[1].ssrc(), 3333);
+        assert_eq!(blocks[1].cumulative_lost(), 256);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_truncated_reports() {
+        // RR claims 2 report blocks but carries only 1
+        let mut buf = vec![0x82, 201, 0, 7];
+        buf.extend_from_slice(&1111_u32.to_be_bytes());
+        buf.extend(report_block(2222, [0, 0, 0]));
+
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        assert!(matches!(
+            RefReceiverReport::try_from(packet),
+            Err(RtpError::NotEnoughBuffer { .. }),
+        ));
+
+        assert!(matches!(
+            RefSenderReport::try_from(packet),
+            Err(RtpError::UnexpectedRtcpType(201)),
+        ));
+    }
+}
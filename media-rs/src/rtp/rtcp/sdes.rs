@@ -0,0 +1,401 @@
+//! https://datatracker.ietf.org/doc/html/rfc3550#section-6.5
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::error::RtpError;
+
+use super::{RefRtcpPacket, RtcpBuilder, RtcpPacketBuilder, RtcpType};
+
+
+/*
+        0                   1                   2                   3
+        0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+       +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+header |V=2|P|    SC   |  PT=SDES=202  |             length            |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+chunk  |                          SSRC/CSRC_1                          |
+  1    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                           SDES items                          |
+       |                              ...                              |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+chunk  |                          SSRC/CSRC_2                          |
+  2    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+       |                           SDES items                          |
+       |                              ...                              |
+       +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+
+    item: type(8), length(8), text(length)
+
+    The list of items in each chunk MUST be terminated by one or more null octets,
+    and padded with null octets to the next 32-bit boundary.
+*/
+
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SdesType {
+    End = 0,
+    Cname = 1,
+    Name = 2,
+    Email = 3,
+    Phone = 4,
+    Loc = 5,
+    Tool = 6,
+    Note = 7,
+    Priv = 8,
+}
+
+impl SdesType {
+    pub fn from_num(num: u8) -> Option<Self> {
+        match num {
+            0 => Some(Self::End),
+            1 => Some(Self::Cname),
+            2 => Some(Self::Name),
+            3 => Some(Self::Email),
+            4 => Some(Self::Phone),
+            5 => Some(Self::Loc),
+            6 => Some(Self::Tool),
+            7 => Some(Self::Note),
+            8 => Some(Self::Priv),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Clone, Copy)]
+pub struct RefSdes<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefSdes<'a> {
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    pub fn chunk_iter(&self) -> impl Iterator<Item = RefSdesChunk<'a>> + 'a {
+        SdesChunkIter {
+            buf: self.packet.body(),
+        }
+        .take(self.packet.header().r_count() as usize)
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefSdes<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let header = packet.header();
+        if header.payload_type() != RtcpType::SourceDescription as u8 {
+            return Err(RtpError::UnexpectedRtcpType(header.payload_type()));
+        }
+
+        let mut buf = packet.body();
+        for _ in 0..header.r_count() {
+            let (_items_end, chunk_len) = check_chunk(buf)?;
+            buf = &buf[chunk_len..];
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefSdes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.chunk_iter()).finish()
+    }
+}
+
+
+
+#[derive(Clone, Copy)]
+pub struct RefSdesChunk<'a> {
+    /// ssrc and items, terminator excluded
+    buf: &'a [u8],
+}
+
+impl<'a> RefSdesChunk<'a> {
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        (&self.buf[0..]).get_u32()
+    }
+
+    /// Yield (item type, text)
+    pub fn item_iter(&self) -> impl Iterator<Item = (u8, &'a [u8])> + 'a {
+        SdesItemIter {
+            buf: &self.buf[4..],
+        }
+    }
+
+    pub fn item(&self, item_type: SdesType) -> Option<&'a [u8]> {
+        self.item_iter()
+            .find(|x| x.0 == item_type as u8)
+            .map(|x| x.1)
+    }
+
+    #[inline]
+    pub fn cname(&self) -> Option<&'a [u8]> {
+        self.item(SdesType::Cname)
+    }
+}
+
+impl<'a> fmt::Debug for RefSdesChunk<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let mut d = f.debug_struct("SdesChunk");
+        d.field("ssrc", &self.ssrc());
+        for (item_type, text) in self.item_iter() {
+            match SdesType::from_num(item_type) {
+                Some(t) => d.field(&format!("{t:?}"), &String::from_utf8_lossy(text)),
+                None => d.field(&format!("{item_type}"), &text.len()),
+            };
+        }
+        d.finish()
+    }
+}
+
+/// Split PRIV item text into (prefix, value)
+pub fn parse_sdes_priv(text: &[u8]) -> Result<(&[u8], &[u8]), RtpError> {
+    if text.is_empty() {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: 1,
+            actual: 0,
+            origin: "Rtcp SDES PRIV prefix length",
+        });
+    }
+
+    let prefix_len = text[0] as usize;
+    let text = &text[1..];
+    if text.len() < prefix_len {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: prefix_len,
+            actual: text.len(),
+            origin: "Rtcp SDES PRIV prefix",
+        });
+    }
+
+    Ok(text.split_at(prefix_len))
+}
+
+/// Return (items end, chunk length with terminator and padding)
+fn check_chunk(buf: &[u8]) -> Result<(usize, usize), RtpError> {
+    if buf.len() < 4 {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: 4,
+            actual: buf.len(),
+            origin: "Rtcp SDES chunk ssrc",
+        });
+    }
+
+    let mut offset = 4;
+    loop {
+        if offset >= buf.len() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: offset + 1,
+                actual: buf.len(),
+                origin: "Rtcp SDES terminator",
+            });
+        }
+
+        if buf[offset] == SdesType::End as u8 {
+            break;
+        }
+
+        if offset + 2 > buf.len() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: offset + 2,
+                actual: buf.len(),
+                origin: "Rtcp SDES item header",
+            });
+        }
+
+        let end = offset + 2 + buf[offset + 1] as usize;
+        if end > buf.len() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: end,
+                actual: buf.len(),
+                origin: "Rtcp SDES item text",
+            });
+        }
+        offset = end;
+    }
+
+    let items_end = offset;
+
+    // one or more null octets up to 32 bits boundary
+    let chunk_len = (items_end / 4 + 1) * 4;
+    if chunk_len > buf.len() {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: chunk_len,
+            actual: buf.len(),
+            origin: "Rtcp SDES chunk padding",
+        });
+    }
+
+    if buf[items_end..chunk_len].iter().any(|x| *x != 0) {
+        return Err(RtpError::InvalidSdesTerminator);
+    }
+
+    Ok((items_end, chunk_len))
+}
+
+fn items_end_uncheck(buf: &[u8]) -> usize {
+    let mut offset = 4;
+    while buf[offset] != SdesType::End as u8 {
+        offset += 2 + buf[offset + 1] as usize;
+    }
+    offset
+}
+
+struct SdesChunkIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for SdesChunkIter<'a> {
+    type Item = RefSdesChunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.len() < 4 {
+            return None;
+        }
+
+        let items_end = items_end_uncheck(self.buf);
+        let item = RefSdesChunk {
+            buf: &self.buf[..items_end],
+        };
+        self.buf = &self.buf[(items_end / 4 + 1) * 4..];
+        Some(item)
+    }
+}
+
+struct SdesItemIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for SdesItemIter<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+
+        let item_type = self.buf[0];
+        let end = 2 + self.buf[1] as usize;
+        let text = &self.buf[2..end];
+        self.buf = &self.buf[end..];
+        Some((item_type, text))
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// Begin SDES packet, write chunks with [`RtcpPacketBuilder::write_sdes_chunk`]
+    pub fn sdes<'b>(&'b mut self) -> RtcpPacketBuilder<'b, 'a> {
+        self.packet(RtcpType::SourceDescription as u8)
+    }
+
+    pub fn sdes_cname(&mut self, ssrc: u32, cname: &[u8]) -> Result<(), RtpError> {
+        self.sdes().write_sdes_chunk(ssrc, &[(SdesType::Cname, cname)])?;
+        Ok(())
+    }
+}
+
+impl<'a, 'b> RtcpPacketBuilder<'a, 'b> {
+
+    /// Append a SDES chunk with terminator and increase source count
+    pub fn write_sdes_chunk(&mut self, ssrc: u32, items: &[(SdesType, &[u8])]) -> Result<&mut Self, RtpError> {
+        self.inc_count()?.write_u32(ssrc);
+
+        for (item_type, text) in items {
+            assert!(*item_type != SdesType::End, "invalid RTCP SDES item type [End]");
+            assert!(text.len() < 256, "invalid RTCP SDES item length [{}]", text.len());
+
+            self
+            .write_u8(*item_type as u8)
+            .write_u8(text.len() as u8)
+            .write_slice(text);
+        }
+
+        Ok(self
+        .write_u8(SdesType::End as u8)
+        .align())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, RefRtcpPacket, RefRtcpPackets, RtcpBuilder};
+
+    use super::{parse_sdes_priv, RefSdes, SdesType};
+
+    #[test]
+    fn test_build_sdes() {
+        let mut buf = vec![0_u8; 1500];
+
+        let mut builder = RtcpBuilder::new(&mut buf);
+
+        builder.sdes()
+        .write_sdes_chunk(1111, &[(SdesType::Cname, b"abcd"), (SdesType::Tool, b"media-rs")]).unwrap()
+        .write_sdes_chunk(2222, &[(SdesType::Priv, b"\x03keyvalue")]).unwrap()
+        .write_sdes_chunk(3333, &[]).unwrap();
+
+        builder.sdes_cname(4444, b"user@host").unwrap();
+
+        let len = builder.finish().unwrap();
+
+        let packets = RefRtcpPackets::try_from(&buf[..len]).unwrap();
+        let mut iter = packets.uncheck_iter();
+
+        let sdes = RefSdes::try_from(iter.next().unwrap()).unwrap();
+        let chunks: Vec<_> = sdes.chunk_iter().collect();
+        assert_eq!(chunks.len(), 3);
+
+        assert_eq!(chunks[0].ssrc(), 1111);
+        assert_eq!(chunks[0].cname(), Some(&b"abcd"[..]));
+        assert_eq!(chunks[0].item(SdesType::Tool), Some(&b"media-rs"[..]));
+
+        assert_eq!(chunks[1].ssrc(), 2222);
+        assert_eq!(chunks[1].cname(), None);
+        let text = chunks[1].item(SdesType::Priv).unwrap();
+        assert_eq!(parse_sdes_priv(text).unwrap(), (&b"key"[..], &b"value"[..]));
+
+        assert_eq!(chunks[2].ssrc(), 3333);
+        assert_eq!(chunks[2].item_iter().count(), 0);
+
+        let sdes = RefSdes::try_from(iter.next().unwrap()).unwrap();
+        let chunk = sdes.chunk_iter().next().unwrap();
+        assert_eq!(chunk.ssrc(), 4444);
+        assert_eq!(chunk.cname(), Some(&b"user@host"[..]));
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_sdes() {
+        // item length exceeds chunk
+        let buf = [0x81, 202, 0, 2, 0, 0, 0, 1, 1, 5, b'a', b'b'];
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        assert!(matches!(RefSdes::try_from(packet), Err(RtpError::NotEnoughBuffer { .. })));
+
+        // missing terminator
+        let buf = [0x81, 202, 0, 2, 0, 0, 0, 1, 1, 2, b'a', b'b'];
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        assert!(matches!(RefSdes::try_from(packet), Err(RtpError::NotEnoughBuffer { .. })));
+
+        // non-null padding
+        let buf = [0x81, 202, 0, 2, 0, 0, 0, 1, 1, 1, b'a', 0, 0, 1, 0, 0];
+        let packet = RefRtcpPacket::try_from(&buf[..12]).unwrap();
+        assert!(RefSdes::try_from(packet).is_ok());
+        let buf = [0x81, 202, 0, 3, 0, 0, 0, 1, 1, 2, b'a', b'b', 0, 1, 0, 0];
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        assert!(matches!(RefSdes::try_from(packet), Err(RtpError::InvalidSdesTerminator)));
+    }
+}
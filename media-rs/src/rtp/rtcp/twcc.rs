@@ -0,0 +1,439 @@
+//! https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01#section-3.1
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::{error::RtpError, Seq};
+
+use super::{RefFeedback, RtcpBuilder, RtcpType};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |V=2|P|  FMT=15 |    PT=205     |           length              |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                     SSRC of packet sender                     |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                      SSRC of media source                     |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |      base sequence number     |      packet status count      |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                 reference time                | fb pkt. count |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |          packet chunk         |         packet chunk          |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   .                                                               .
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |         packet chunk          |  recv delta   |  recv delta   |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   .                                                               .
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |           recv delta          |  recv delta   | zero padding  |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    run length chunk:    0 | S(2) | run length(13)
+    status vector chunk: 1 | S(1) | symbol list(14),
+                            S=0: 14 symbols of 1 bit,
+                            S=1: 7 symbols of 2 bits
+
+    reference time: signed 24 bits, multiples of 64ms
+    recv delta: multiples of 250us,
+                small delta 1 byte unsigned, large delta 2 bytes signed
+*/
+
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TwccStatus {
+    NotReceived = 0,
+    SmallDelta = 1,
+    LargeDelta = 2,
+}
+
+impl TwccStatus {
+    pub fn from_num(num: u8) -> Option<Self> {
+        match num {
+            0 => Some(Self::NotReceived),
+            1 => Some(Self::SmallDelta),
+            2 => Some(Self::LargeDelta),
+            _ => None,
+        }
+    }
+
+    pub fn from_delta(delta: Option<i16>) -> Self {
+        match delta {
+            None => Self::NotReceived,
+            Some(d) if (0..=255).contains(&d) => Self::SmallDelta,
+            Some(_d) => Self::LargeDelta,
+        }
+    }
+
+    #[inline]
+    fn delta_len(&self) -> usize {
+        *self as usize
+    }
+}
+
+
+#[derive(Clone, Copy)]
+pub struct RefTwcc<'a> {
+    fb: RefFeedback<'a>,
+    /// offset of recv deltas in FCI
+    deltas_offset: usize,
+}
+
+impl<'a> RefTwcc<'a> {
+
+    pub const FMT: u8 = 15;
+
+    pub const FIXED_LEN: usize = 8;
+
+    pub const DELTA_UNIT_US: i64 = 250;
+
+    pub const REFERENCE_TIME_UNIT_MS: i64 = 64;
+
+    #[inline]
+    pub fn feedback(&self) -> RefFeedback<'a> {
+        self.fb
+    }
+
+    #[inline]
+    pub fn sender_ssrc(&self) -> u32 {
+        self.fb.sender_ssrc()
+    }
+
+    #[inline]
+    pub fn media_ssrc(&self) -> u32 {
+        self.fb.media_ssrc()
+    }
+
+    #[inline]
+    pub fn base_seq(&self) -> Seq {
+        Seq((&self.fb.fci()[0..]).get_u16())
+    }
+
+    #[inline]
+    pub fn status_count(&self) -> u16 {
+        (&self.fb.fci()[2..]).get_u16()
+    }
+
+    /// signed 24 bits, multiples of 64ms
+    #[inline]
+    pub fn reference_time(&self) -> i32 {
+        ((&self.fb.fci()[4..]).get_u32() as i32) >> 8
+    }
+
+    #[inline]
+    pub fn reference_time_ms(&self) -> i64 {
+        self.reference_time() as i64 * Self::REFERENCE_TIME_UNIT_MS
+    }
+
+    #[inline]
+    pub fn fb_pkt_count(&self) -> u8 {
+        self.fb.fci()[7]
+    }
+
+    /// Yield (transport sequence, recv delta in multiples of 250us) for each packet,
+    /// delta is None if not received
+    pub fn iter(&self) -> TwccIter<'a> {
+        let fci = self.fb.fci();
+        TwccIter {
+            chunks: &fci[Self::FIXED_LEN..self.deltas_offset],
+            deltas: &fci[self.deltas_offset..],
+            seq: self.base_seq(),
+            remaining: self.status_count(),
+            chunk: 0,
+            capacity: 0,
+            pos: 0,
+        }
+    }
+}
+
+impl<'a> TryFrom<RefFeedback<'a>> for RefTwcc<'a> {
+    type Error = RtpError;
+
+    fn try_from(fb: RefFeedback<'a>) -> Result<Self, Self::Error> {
+        let pt = fb.packet().header().payload_type();
+        if pt != RtcpType::TransportFeedback as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        if fb.fmt() != Self::FMT {
+            return Err(RtpError::UnexpectedFeedbackFmt(fb.fmt()));
+        }
+
+        let deltas_offset = check_fci(fb.fci())?;
+
+        Ok(Self { fb, deltas_offset })
+    }
+}
+
+impl<'a> fmt::Debug for RefTwcc<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Twcc")
+            .field("sender_ssrc", &self.sender_ssrc())
+            .field("media_ssrc", &self.media_ssrc())
+            .field("base_seq", &self.base_seq().0)
+            .field("status_count", &self.status_count())
+            .field("reference_time", &self.reference_time())
+            .field("fb_pkt_count", &self.fb_pkt_count())
+            .finish()
+    }
+}
+
+/// Return (capacity, status of pos)
+#[inline]
+fn chunk_status(chunk: u16, pos: u16) -> (u16, u8) {
+    if chunk & 0x8000 == 0 {
+        // run length
+        (chunk & 0x1FFF, ((chunk >> 13) & 0x03) as u8)
+    } else if chunk & 0x4000 == 0 {
+        // 1 bit symbols
+        (14, ((chunk >> (13 - pos)) & 0x01) as u8)
+    } else {
+        // 2 bits symbols
+        (7, ((chunk >> (12 - 2 * pos)) & 0x03) as u8)
+    }
+}
+
+/// Return offset of recv deltas
+fn check_fci(fci: &[u8]) -> Result<usize, RtpError> {
+    if fci.len() < RefTwcc::FIXED_LEN {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: RefTwcc::FIXED_LEN,
+            actual: fci.len(),
+            origin: "Rtcp TWCC fixed fields",
+        });
+    }
+
+    let mut remaining = (&fci[2..]).get_u16();
+    let mut offset = RefTwcc::FIXED_LEN;
+    let mut deltas_len = 0;
+
+    while remaining > 0 {
+        if offset + 2 > fci.len() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: offset + 2,
+                actual: fci.len(),
+                origin: "Rtcp TWCC packet chunk",
+            });
+        }
+
+        let chunk = (&fci[offset..]).get_u16();
+        offset += 2;
+
+        let (capacity, _status) = chunk_status(chunk, 0);
+        let num = capacity.min(remaining);
+        for pos in 0..num {
+            let (_capacity, status) = chunk_status(chunk, pos);
+            let status = TwccStatus::from_num(status).ok_or(RtpError::InvalidTwccStatus(status))?;
+            deltas_len += status.delta_len();
+        }
+        remaining -= num;
+    }
+
+    if offset + deltas_len > fci.len() {
+        return Err(RtpError::NotEnoughBuffer {
+            expect: offset + deltas_len,
+            actual: fci.len(),
+            origin: "Rtcp TWCC recv deltas",
+        });
+    }
+
+    Ok(offset)
+}
+
+
+pub struct TwccIter<'a> {
+    chunks: &'a [u8],
+    deltas: &'a [u8],
+    seq: Seq,
+    remaining: u16,
+    chunk: u16,
+    capacity: u16,
+    pos: u16,
+}
+
+impl<'a> Iterator for TwccIter<'a> {
+    type Item = (Seq, Option<i16>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        while self.pos >= self.capacity {
+            self.chunk = self.chunks.get_u16();
+            self.capacity = chunk_status(self.chunk, 0).0;
+            self.pos = 0;
+        }
+
+        let (_capacity, status) = chunk_status(self.chunk, self.pos);
+        let delta = match TwccStatus::from_num(status) {
+            Some(TwccStatus::SmallDelta) => Some(self.deltas.get_u8() as i16),
+            Some(TwccStatus::LargeDelta) => Some(self.deltas.get_i16()),
+            _ => None,
+        };
+
+        let seq = self.seq;
+        self.seq = self.seq.next();
+        self.pos += 1;
+        self.remaining -= 1;
+
+        Some((seq, delta))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// deltas[i] is recv delta of packet base_seq+i in multiples of 250us, None if not received.
+    ///
+    /// The first delta is relative to reference time, the others relative to the previous received packet.
+    pub fn twcc(
+        &mut self,
+        sender_ssrc: u32,
+        media_ssrc: u32,
+        base_seq: Seq,
+        reference_time: i32,
+        fb_pkt_count: u8,
+        deltas: &[Option<i16>],
+    ) -> Result<(), RtpError> {
+        assert!(deltas.len() <= u16::MAX as usize, "too many TWCC packets [{}]", deltas.len());
+
+        let mut builder = self.feedback(RtcpType::TransportFeedback, RefTwcc::FMT, sender_ssrc, media_ssrc)?;
+        builder
+        .write_u16(base_seq.0)
+        .write_u16(deltas.len() as u16)
+        .write_u32((reference_time as u32) << 8 | fb_pkt_count as u32);
+
+        let status: Vec<TwccStatus> = deltas.iter().map(|x| TwccStatus::from_delta(*x)).collect();
+        let mut status = &status[..];
+        while !status.is_empty() {
+            let (chunk, num) = encode_chunk(status);
+            builder.write_u16(chunk);
+            status = &status[num..];
+        }
+
+        for delta in deltas.iter().flatten() {
+            match TwccStatus::from_delta(Some(*delta)) {
+                TwccStatus::SmallDelta => builder.write_u8(*delta as u8),
+                _ => builder.write_u16(*delta as u16),
+            };
+        }
+
+        builder.align();
+        Ok(())
+    }
+}
+
+/// Choose the most compact chunk for the head of status, return (chunk, number of status encoded)
+fn encode_chunk(status: &[TwccStatus]) -> (u16, usize) {
+    let first = status[0];
+    let run = status.iter()
+        .take(0x1FFF)
+        .take_while(|x| **x == first)
+        .count();
+
+    if run >= 14 {
+        return (run_length_chunk(first, run), run);
+    }
+
+    let head = &status[..status.len().min(14)];
+    if head.iter().all(|x| *x != TwccStatus::LargeDelta) {
+        let mut chunk = 0x8000_u16;
+        for (pos, s) in head.iter().enumerate() {
+            chunk |= (*s as u16) << (13 - pos);
+        }
+        return (chunk, head.len());
+    }
+
+    if run >= 7 {
+        return (run_length_chunk(first, run), run);
+    }
+
+    let head = &status[..status.len().min(7)];
+    let mut chunk = 0xC000_u16;
+    for (pos, s) in head.iter().enumerate() {
+        chunk |= (*s as u16) << (12 - 2 * pos);
+    }
+    (chunk, head.len())
+}
+
+#[inline]
+fn run_length_chunk(status: TwccStatus, run: usize) -> u16 {
+    (status as u16) << 13 | run as u16
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPacket, RefRtpfb, RtcpBuilder, Seq};
+
+    use super::{encode_chunk, TwccStatus};
+
+    #[test]
+    fn test_encode_chunk() {
+        use TwccStatus::*;
+
+        // long run
+        let status = vec![NotReceived; 20];
+        assert_eq!(encode_chunk(&status), (0x0014, 20));
+
+        // mixed small and lost
+        let status = [SmallDelta, NotReceived, SmallDelta];
+        assert_eq!(encode_chunk(&status), ((1 << 15) | (1 << 13) | (1 << 11), 3));
+
+        // large delta requires 2 bits symbols
+        let status = [LargeDelta, SmallDelta, NotReceived];
+        assert_eq!(encode_chunk(&status), ((1 << 15) | (1 << 14) | (2 << 12) | (1 << 10), 3));
+
+        // run of large delta
+        let status = vec![LargeDelta; 8];
+        assert_eq!(encode_chunk(&status), (0x4008, 8));
+    }
+
+    #[test]
+    fn test_build_twcc() {
+        let mut deltas = vec![];
+        deltas.extend([Some(4_i16), Some(0), None, Some(255)]);
+        deltas.extend(vec![None; 30]);
+        deltas.extend([Some(256_i16), Some(-1), Some(1000), Some(3)]);
+        deltas.extend(vec![Some(1); 20]);
+        deltas.extend([Some(i16::MIN), Some(i16::MAX)]);
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.twcc(1111, 2222, Seq(65530), -3, 9, &deltas).unwrap();
+        let len = builder.finish().unwrap();
+
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        let RefRtpfb::Twcc(twcc) = RefRtpfb::try_from(packet).unwrap() else {
+            panic!("expect twcc");
+        };
+
+        assert_eq!(twcc.sender_ssrc(), 1111);
+        assert_eq!(twcc.media_ssrc(), 2222);
+        assert_eq!(twcc.base_seq(), Seq(65530));
+        assert_eq!(twcc.status_count() as usize, deltas.len());
+        assert_eq!(twcc.reference_time(), -3);
+        assert_eq!(twcc.reference_time_ms(), -192);
+        assert_eq!(twcc.fb_pkt_count(), 9);
+
+        let mut seq = Seq(65530);
+        let mut iter = twcc.iter();
+        for delta in deltas.iter() {
+            assert_eq!(iter.next(), Some((seq, *delta)));
+            seq = seq.next();
+        }
+        assert_eq!(iter.next(), None);
+    }
+}
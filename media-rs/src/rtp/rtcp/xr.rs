@@ -0,0 +1,859 @@
+//! https://datatracker.ietf.org/doc/html/rfc3611
+//!
+
+use std::fmt;
+use bytes::Buf;
+
+use crate::rtp::{error::RtpError, NtpTime, Seq};
+
+use super::{RefRtcpPacket, RtcpBuilder, RtcpPacketBuilder, RtcpType};
+
+
+/*
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |V=2|P|reserved |   PT=XR=207   |             length            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                              SSRC                             |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   :                         report blocks                         :
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    report block
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |      BT       | type-specific |         block length          |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   :             type-specific block contents                      :
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    block length in 32 bits words, header excluded
+*/
+
+
+#[derive(Clone, Copy)]
+pub struct RefXr<'a> {
+    packet: RefRtcpPacket<'a>,
+}
+
+impl<'a> RefXr<'a> {
+
+    #[inline]
+    pub fn packet(&self) -> RefRtcpPacket<'a> {
+        self.packet
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.packet.header().ssrc()
+    }
+
+    pub fn block_iter(&self) -> impl Iterator<Item = XrBlock<'a>> + 'a {
+        XrBlockIter {
+            buf: self.packet.payload(),
+        }
+        .map(XrBlock::from_raw)
+    }
+}
+
+impl<'a> TryFrom<RefRtcpPacket<'a>> for RefXr<'a> {
+    type Error = RtpError;
+
+    fn try_from(packet: RefRtcpPacket<'a>) -> Result<Self, Self::Error> {
+        let pt = packet.header().payload_type();
+        if pt != RtcpType::ExtendedReport as u8 {
+            return Err(RtpError::UnexpectedRtcpType(pt));
+        }
+
+        let mut buf = packet.payload();
+        while !buf.is_empty() {
+            if buf.len() < RefXrBlock::HEADER_LEN {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: RefXrBlock::HEADER_LEN,
+                    actual: buf.len(),
+                    origin: "Rtcp XR block header",
+                });
+            }
+
+            let block = RefXrBlock { buf };
+            let block_len = block.block_len();
+            if buf.len() < block_len {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: block_len,
+                    actual: buf.len(),
+                    origin: "Rtcp XR block length",
+                });
+            }
+
+            let block = RefXrBlock { buf: &buf[..block_len] };
+            let min_len = match XrBlockType::from_num(block.block_type()) {
+                Some(XrBlockType::LossRle) | Some(XrBlockType::DuplicateRle) => RefRleBlock::MIN_LEN,
+                Some(XrBlockType::Rrtr) => RefRrtr::LEN,
+                Some(XrBlockType::StatsSummary) => RefStatsSummary::LEN,
+                Some(XrBlockType::VoipMetrics) => RefVoipMetrics::LEN,
+                Some(XrBlockType::Dlrr) | None => 0,
+            };
+
+            if block.body().len() < min_len {
+                return Err(RtpError::NotEnoughBuffer {
+                    expect: min_len,
+                    actual: block.body().len(),
+                    origin: "Rtcp XR block body",
+                });
+            }
+
+            buf = &buf[block_len..];
+        }
+
+        Ok(Self { packet })
+    }
+}
+
+impl<'a> fmt::Debug for RefXr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Xr")
+            .field("ssrc", &self.ssrc())
+            .field("blocks", &self.block_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum XrBlockType {
+    LossRle = 1,
+    DuplicateRle = 2,
+    Rrtr = 4,
+    Dlrr = 5,
+    StatsSummary = 6,
+    VoipMetrics = 7,
+}
+
+impl XrBlockType {
+    pub fn from_num(num: u8) -> Option<Self> {
+        match num {
+            1 => Some(Self::LossRle),
+            2 => Some(Self::DuplicateRle),
+            4 => Some(Self::Rrtr),
+            5 => Some(Self::Dlrr),
+            6 => Some(Self::StatsSummary),
+            7 => Some(Self::VoipMetrics),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum XrBlock<'a> {
+    LossRle(RefRleBlock<'a>),
+    DuplicateRle(RefRleBlock<'a>),
+    Rrtr(RefRrtr<'a>),
+    Dlrr(RefDlrr<'a>),
+    StatsSummary(RefStatsSummary<'a>),
+    VoipMetrics(RefVoipMetrics<'a>),
+    Unknown(RefXrBlock<'a>),
+}
+
+impl<'a> XrBlock<'a> {
+    fn from_raw(block: RefXrBlock<'a>) -> Self {
+        match XrBlockType::from_num(block.block_type()) {
+            Some(XrBlockType::LossRle) => Self::LossRle(RefRleBlock { block }),
+            Some(XrBlockType::DuplicateRle) => Self::DuplicateRle(RefRleBlock { block }),
+            Some(XrBlockType::Rrtr) => Self::Rrtr(RefRrtr { block }),
+            Some(XrBlockType::Dlrr) => Self::Dlrr(RefDlrr { block }),
+            Some(XrBlockType::StatsSummary) => Self::StatsSummary(RefStatsSummary { block }),
+            Some(XrBlockType::VoipMetrics) => Self::VoipMetrics(RefVoipMetrics { block }),
+            None => Self::Unknown(block),
+        }
+    }
+}
+
+
+/// Any report block, header included
+#[derive(Clone, Copy)]
+pub struct RefXrBlock<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> RefXrBlock<'a> {
+
+    pub const HEADER_LEN: usize = 4;
+
+    #[inline]
+    pub fn block_type(&self) -> u8 {
+        self.buf[0]
+    }
+
+    #[inline]
+    pub fn type_specific(&self) -> u8 {
+        self.buf[1]
+    }
+
+    /// Whole block length in bytes, header included
+    #[inline]
+    pub fn block_len(&self) -> usize {
+        Self::HEADER_LEN + 4 * (&self.buf[2..]).get_u16() as usize
+    }
+
+    #[inline]
+    pub fn body(&self) -> &'a [u8] {
+        &self.buf[Self::HEADER_LEN..]
+    }
+}
+
+impl<'a> fmt::Debug for RefXrBlock<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("XrBlock")
+            .field("block_type", &self.block_type())
+            .field("type_specific", &self.type_specific())
+            .field("body_length", &self.body().len())
+            .finish()
+    }
+}
+
+struct XrBlockIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Iterator for XrBlockIter<'a> {
+    type Item = RefXrBlock<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buf.is_empty() {
+            return None;
+        }
+
+        let len = RefXrBlock { buf: self.buf }.block_len();
+        let block = RefXrBlock { buf: &self.buf[..len] };
+        self.buf = &self.buf[len..];
+        Some(block)
+    }
+}
+
+
+
+/*
+    Loss RLE (BT=1) / Duplicate RLE (BT=2)
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     BT=1      | rsvd. |   T   |         block length          |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                        SSRC of source                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |          begin_seq            |             end_seq           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |          chunk 1              |             chunk 2           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RleChunk {
+    /// run of packets received(true) or lost(false), 14 bits length
+    RunLength { run_type: bool, length: u16 },
+    /// 15 bits, most significant bit first
+    BitVector(u16),
+    Terminating,
+}
+
+impl RleChunk {
+    fn from_u16(v: u16) -> Self {
+        if v == 0 {
+            Self::Terminating
+        } else if v & 0x8000 == 0 {
+            Self::RunLength {
+                run_type: v & 0x4000 != 0,
+                length: v & 0x3FFF,
+            }
+        } else {
+            Self::BitVector(v & 0x7FFF)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct RefRleBlock<'a> {
+    block: RefXrBlock<'a>,
+}
+
+impl<'a> RefRleBlock<'a> {
+
+    pub const MIN_LEN: usize = 8;
+
+    #[inline]
+    pub fn thinning(&self) -> u8 {
+        self.block.type_specific() & 0x0F
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        (&self.block.body()[0..]).get_u32()
+    }
+
+    #[inline]
+    pub fn begin_seq(&self) -> Seq {
+        Seq((&self.block.body()[4..]).get_u16())
+    }
+
+    /// exclusive
+    #[inline]
+    pub fn end_seq(&self) -> Seq {
+        Seq((&self.block.body()[6..]).get_u16())
+    }
+
+    /// Terminating null chunks are skipped
+    pub fn chunk_iter(&self) -> impl Iterator<Item = RleChunk> + 'a {
+        self.block.body()[Self::MIN_LEN..]
+            .chunks_exact(2)
+            .map(|mut b| RleChunk::from_u16(b.get_u16()))
+            .filter(|x| *x != RleChunk::Terminating)
+    }
+}
+
+impl<'a> fmt::Debug for RefRleBlock<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("RleBlock")
+            .field("thinning", &self.thinning())
+            .field("ssrc", &self.ssrc())
+            .field("begin_seq", &self.begin_seq().0)
+            .field("end_seq", &self.end_seq().0)
+            .field("chunks", &self.chunk_iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+
+
+/*
+    Receiver Reference Time (BT=4)
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     BT=4      |   reserved    |       block length = 2        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |              NTP timestamp, most significant word             |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |             NTP timestamp, least significant word             |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+#[derive(Clone, Copy)]
+pub struct RefRrtr<'a> {
+    block: RefXrBlock<'a>,
+}
+
+impl<'a> RefRrtr<'a> {
+
+    pub const LEN: usize = 8;
+
+    #[inline]
+    pub fn ntp_timestamp(&self) -> u64 {
+        (&self.block.body()[0..]).get_u64()
+    }
+
+    #[inline]
+    pub fn ntp_time(&self) -> NtpTime {
+        NtpTime(self.ntp_timestamp())
+    }
+}
+
+impl<'a> fmt::Debug for RefRrtr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("Rrtr")
+            .field("ntp", &self.ntp_timestamp())
+            .finish()
+    }
+}
+
+
+
+/*
+    DLRR (BT=5)
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     BT=5      |   reserved    |         block length          |
+   +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+   |                 SSRC_1 (SSRC of first receiver)               | sub-
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+ block
+   |                         last RR (LRR)                         |   1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                   delay since last RR (DLRR)                  |
+   +=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+=+
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DlrrItem {
+    pub ssrc: u32,
+    /// middle 32 bits of NTP timestamp in RRTR
+    pub lrr: u32,
+    /// in units of 1/65536 seconds
+    pub dlrr: u32,
+}
+
+impl DlrrItem {
+    pub const LEN: usize = 12;
+}
+
+#[derive(Clone, Copy)]
+pub struct RefDlrr<'a> {
+    block: RefXrBlock<'a>,
+}
+
+impl<'a> RefDlrr<'a> {
+    pub fn item_iter(&self) -> impl Iterator<Item = DlrrItem> + 'a {
+        self.block.body()
+            .chunks_exact(DlrrItem::LEN)
+            .map(|mut b| DlrrItem {
+                ssrc: b.get_u32(),
+                lrr: b.get_u32(),
+                dlrr: b.get_u32(),
+            })
+    }
+}
+
+impl<'a> fmt::Debug for RefDlrr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.item_iter()).finish()
+    }
+}
+
+
+
+/*
+    Statistics Summary (BT=6)
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     BT=6      |L|D|J|ToH|rsvd.|       block length = 9        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                        SSRC of source                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |          begin_seq            |             end_seq           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                        lost_packets                           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                        dup_packets                            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                         min_jitter                            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                         max_jitter                            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                         mean_jitter                           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                         dev_jitter                            |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   | min_ttl_or_hl | max_ttl_or_hl |mean_ttl_or_hl | dev_ttl_or_hl |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+#[derive(Clone, Copy)]
+pub struct RefStatsSummary<'a> {
+    block: RefXrBlock<'a>,
+}
+
+impl<'a> RefStatsSummary<'a> {
+
+    pub const LEN: usize = 36;
+
+    #[inline]
+    pub fn has_loss(&self) -> bool {
+        self.block.type_specific() & 0x80 != 0
+    }
+
+    #[inline]
+    pub fn has_duplicate(&self) -> bool {
+        self.block.type_specific() & 0x40 != 0
+    }
+
+    #[inline]
+    pub fn has_jitter(&self) -> bool {
+        self.block.type_specific() & 0x20 != 0
+    }
+
+    /// 0: none, 1: IPv4 TTL, 2: IPv6 hop limit
+    #[inline]
+    pub fn ttl_or_hop_limit(&self) -> u8 {
+        (self.block.type_specific() >> 3) & 0x03
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        (&self.block.body()[0..]).get_u32()
+    }
+
+    #[inline]
+    pub fn begin_seq(&self) -> Seq {
+        Seq((&self.block.body()[4..]).get_u16())
+    }
+
+    #[inline]
+    pub fn end_seq(&self) -> Seq {
+        Seq((&self.block.body()[6..]).get_u16())
+    }
+
+    #[inline]
+    pub fn lost_packets(&self) -> u32 {
+        (&self.block.body()[8..]).get_u32()
+    }
+
+    #[inline]
+    pub fn dup_packets(&self) -> u32 {
+        (&self.block.body()[12..]).get_u32()
+    }
+
+    #[inline]
+    pub fn min_jitter(&self) -> u32 {
+        (&self.block.body()[16..]).get_u32()
+    }
+
+    #[inline]
+    pub fn max_jitter(&self) -> u32 {
+        (&self.block.body()[20..]).get_u32()
+    }
+
+    #[inline]
+    pub fn mean_jitter(&self) -> u32 {
+        (&self.block.body()[24..]).get_u32()
+    }
+
+    #[inline]
+    pub fn dev_jitter(&self) -> u32 {
+        (&self.block.body()[28..]).get_u32()
+    }
+
+    #[inline]
+    pub fn min_ttl_or_hl(&self) -> u8 {
+        self.block.body()[32]
+    }
+
+    #[inline]
+    pub fn max_ttl_or_hl(&self) -> u8 {
+        self.block.body()[33]
+    }
+
+    #[inline]
+    pub fn mean_ttl_or_hl(&self) -> u8 {
+        self.block.body()[34]
+    }
+
+    #[inline]
+    pub fn dev_ttl_or_hl(&self) -> u8 {
+        self.block.body()[35]
+    }
+}
+
+impl<'a> fmt::Debug for RefStatsSummary<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("StatsSummary")
+            .field("ssrc", &self.ssrc())
+            .field("begin_seq", &self.begin_seq().0)
+            .field("end_seq", &self.end_seq().0)
+            .field("lost_packets", &self.lost_packets())
+            .field("dup_packets", &self.dup_packets())
+            .field("mean_jitter", &self.mean_jitter())
+            .finish()
+    }
+}
+
+
+
+/*
+    VoIP Metrics (BT=7)
+
+    0                   1                   2                   3
+    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     BT=7      |   reserved    |       block length = 8        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |                        SSRC of source                         |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |   loss rate   | discard rate  | burst density |  gap density  |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |       burst duration          |         gap duration          |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |     round trip delay          |       end system delay        |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   | signal level  |  noise level  |     RERL      |     Gmin      |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |   R factor    | ext. R factor |    MOS-LQ     |    MOS-CQ     |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |   RX config   |   reserved    |          JB nominal           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+   |          JB maximum           |          JB abs max           |
+   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+*/
+
+#[derive(Clone, Copy)]
+pub struct RefVoipMetrics<'a> {
+    block: RefXrBlock<'a>,
+}
+
+impl<'a> RefVoipMetrics<'a> {
+
+    pub const LEN: usize = 32;
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        (&self.block.body()[0..]).get_u32()
+    }
+
+    #[inline]
+    pub fn loss_rate(&self) -> u8 {
+        self.block.body()[4]
+    }
+
+    #[inline]
+    pub fn discard_rate(&self) -> u8 {
+        self.block.body()[5]
+    }
+
+    #[inline]
+    pub fn burst_density(&self) -> u8 {
+        self.block.body()[6]
+    }
+
+    #[inline]
+    pub fn gap_density(&self) -> u8 {
+        self.block.body()[7]
+    }
+
+    #[inline]
+    pub fn burst_duration(&self) -> u16 {
+        (&self.block.body()[8..]).get_u16()
+    }
+
+    #[inline]
+    pub fn gap_duration(&self) -> u16 {
+        (&self.block.body()[10..]).get_u16()
+    }
+
+    #[inline]
+    pub fn round_trip_delay(&self) -> u16 {
+        (&self.block.body()[12..]).get_u16()
+    }
+
+    #[inline]
+    pub fn end_system_delay(&self) -> u16 {
+        (&self.block.body()[14..]).get_u16()
+    }
+
+    /// signed, in dBm
+    #[inline]
+    pub fn signal_level(&self) -> i8 {
+        self.block.body()[16] as i8
+    }
+
+    /// signed, in dBm
+    #[inline]
+    pub fn noise_level(&self) -> i8 {
+        self.block.body()[17] as i8
+    }
+
+    #[inline]
+    pub fn rerl(&self) -> u8 {
+        self.block.body()[18]
+    }
+
+    #[inline]
+    pub fn gmin(&self) -> u8 {
+        self.block.body()[19]
+    }
+
+    #[inline]
+    pub fn r_factor(&self) -> u8 {
+        self.block.body()[20]
+    }
+
+    #[inline]
+    pub fn ext_r_factor(&self) -> u8 {
+        self.block.body()[21]
+    }
+
+    /// MOS x 10
+    #[inline]
+    pub fn mos_lq(&self) -> u8 {
+        self.block.body()[22]
+    }
+
+    /// MOS x 10
+    #[inline]
+    pub fn mos_cq(&self) -> u8 {
+        self.block.body()[23]
+    }
+
+    #[inline]
+    pub fn rx_config(&self) -> u8 {
+        self.block.body()[24]
+    }
+
+    #[inline]
+    pub fn jb_nominal(&self) -> u16 {
+        (&self.block.body()[26..]).get_u16()
+    }
+
+    #[inline]
+    pub fn jb_maximum(&self) -> u16 {
+        (&self.block.body()[28..]).get_u16()
+    }
+
+    #[inline]
+    pub fn jb_abs_max(&self) -> u16 {
+        (&self.block.body()[30..]).get_u16()
+    }
+}
+
+impl<'a> fmt::Debug for RefVoipMetrics<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_struct("VoipMetrics")
+            .field("ssrc", &self.ssrc())
+            .field("loss_rate", &self.loss_rate())
+            .field("discard_rate", &self.discard_rate())
+            .field("round_trip_delay", &self.round_trip_delay())
+            .field("mos_lq", &self.mos_lq())
+            .field("mos_cq", &self.mos_cq())
+            .finish()
+    }
+}
+
+
+
+impl<'a> RtcpBuilder<'a> {
+
+    /// Begin XR packet, write blocks with `write_xr_*`
+    pub fn xr<'b>(&'b mut self, ssrc: u32) -> RtcpPacketBuilder<'b, 'a> {
+        let mut builder = self.packet(RtcpType::ExtendedReport as u8);
+        builder.write_u32(ssrc);
+        builder
+    }
+}
+
+impl<'a, 'b> RtcpPacketBuilder<'a, 'b> {
+
+    pub fn write_xr_rrtr(&mut self, ntp_timestamp: u64) -> &mut Self {
+        self
+        .write_u8(XrBlockType::Rrtr as u8)
+        .write_u8(0)
+        .write_u16((RefRrtr::LEN / 4) as u16)
+        .write_u64(ntp_timestamp)
+    }
+
+    pub fn write_xr_dlrr(&mut self, items: &[DlrrItem]) -> &mut Self {
+        self
+        .write_u8(XrBlockType::Dlrr as u8)
+        .write_u8(0)
+        .write_u16((items.len() * DlrrItem::LEN / 4) as u16);
+
+        for item in items {
+            self
+            .write_u32(item.ssrc)
+            .write_u32(item.lrr)
+            .write_u32(item.dlrr);
+        }
+        self
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{RefRtcpPacket, RtcpBuilder, Seq};
+
+    use super::{DlrrItem, RefXr, RleChunk, XrBlock};
+
+    #[test]
+    fn test_build_xr() {
+        let items = [
+            DlrrItem { ssrc: 2222, lrr: 0x0304_0506, dlrr: 65536 },
+            DlrrItem { ssrc: 3333, lrr: 0x0708_0900, dlrr: 1 },
+        ];
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtcpBuilder::new(&mut buf);
+        builder.xr(1111)
+        .write_xr_rrtr(0x0102_0304_0506_0708)
+        .write_xr_dlrr(&items);
+        let len = builder.finish().unwrap();
+
+        let packet = RefRtcpPacket::try_from(&buf[..len]).unwrap();
+        let xr = RefXr::try_from(packet).unwrap();
+        assert_eq!(xr.ssrc(), 1111);
+
+        let mut iter = xr.block_iter();
+
+        let XrBlock::Rrtr(rrtr) = iter.next().unwrap() else { panic!() };
+        assert_eq!(rrtr.ntp_timestamp(), 0x0102_0304_0506_0708);
+
+        let XrBlock::Dlrr(dlrr) = iter.next().unwrap() else { panic!() };
+        assert!(dlrr.item_iter().eq(items));
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_xr() {
+        let mut buf = vec![0x80, 207, 0, 0];
+        buf.extend_from_slice(&1111_u32.to_be_bytes());
+
+        // loss rle, thinning 2
+        buf.extend_from_slice(&[1, 2, 0, 4]);
+        buf.extend_from_slice(&2222_u32.to_be_bytes());
+        buf.extend_from_slice(&[0, 10, 0, 40]);
+        buf.extend_from_slice(&[0x40, 0x05, 0x80, 0x01, 0, 0, 0, 0]);
+
+        // unknown
+        buf.extend_from_slice(&[100, 0, 0, 1, 1, 2, 3, 4]);
+
+        // voip metrics
+        buf.extend_from_slice(&[7, 0, 0, 8]);
+        buf.extend_from_slice(&3333_u32.to_be_bytes());
+        buf.extend_from_slice(&[1, 2, 3, 4, 0, 5, 0, 6, 0, 7, 0, 8, 0xF6, 0xB0, 9, 10, 11, 12, 13, 14, 15, 0, 0, 16, 0, 17, 0, 18]);
+
+        let words = (buf.len() / 4 - 1) as u16;
+        buf[2..4].copy_from_slice(&words.to_be_bytes());
+
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        let xr = RefXr::try_from(packet).unwrap();
+        let mut iter = xr.block_iter();
+
+        let XrBlock::LossRle(rle) = iter.next().unwrap() else { panic!() };
+        assert_eq!(rle.thinning(), 2);
+        assert_eq!(rle.ssrc(), 2222);
+        assert_eq!(rle.begin_seq(), Seq(10));
+        assert_eq!(rle.end_seq(), Seq(40));
+        assert!(rle.chunk_iter().eq([
+            RleChunk::RunLength { run_type: true, length: 5 },
+            RleChunk::BitVector(1),
+        ]));
+
+        let XrBlock::Unknown(block) = iter.next().unwrap() else { panic!() };
+        assert_eq!(block.block_type(), 100);
+        assert_eq!(block.body(), &[1, 2, 3, 4]);
+
+        let XrBlock::VoipMetrics(voip) = iter.next().unwrap() else { panic!() };
+        assert_eq!(voip.ssrc(), 3333);
+        assert_eq!(voip.loss_rate(), 1);
+        assert_eq!(voip.gap_density(), 4);
+        assert_eq!(voip.burst_duration(), 5);
+        assert_eq!(voip.end_system_delay(), 8);
+        assert_eq!(voip.signal_level(), -10);
+        assert_eq!(voip.noise_level(), -80);
+        assert_eq!(voip.mos_cq(), 14);
+        assert_eq!(voip.jb_abs_max(), 18);
+
+        assert!(iter.next().is_none());
+        drop(iter);
+
+        // truncated voip metrics
+        let offset = buf.len() - 36 + 3;
+        buf[offset] = 9;
+        let packet = RefRtcpPacket::try_from(&buf[..]).unwrap();
+        assert!(RefXr::try_from(packet).is_err());
+    }
+}
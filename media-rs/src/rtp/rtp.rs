@@ -1,7 +1,7 @@
-use std::fmt::{self, Write};
+use std::{fmt::{self, Write}, ops::Range};
 use bytes::BufMut;
 
-use super::{error::RtpError, extension::{ExtFormat, ExtIter, WriteExtFns}, Seq, Timestamp};
+use super::{error::RtpError, extension::{ExtFormat, ExtIter, WriteExtFns}, extmap::{ExtMap, ExtValue}, Seq, Timestamp};
 
 
 pub struct RefRtpHeader<'a> {
@@ -246,7 +246,7 @@ impl<'a> RefRtpPacket<'a> {
         }
     }
     
-    pub fn csrc_iter(&self) -> impl Iterator<Item = u32> + '_ {
+    pub fn csrc_iter(&self) -> impl Iterator<Item = u32> + 'a {
         let header = self.header();
 
         self.buf[RefRtpHeader::MIN_LEN..]
@@ -283,6 +283,39 @@ impl<'a> RefRtpPacket<'a> {
         }
     }
     
+    /// The first element with id
+    pub fn ext_by_id(&self, id: u8) -> Option<&'a [u8]> {
+        self.extension_iter()?
+            .find(|(ext_id, _ext)| *ext_id == id)
+            .map(|(_id, ext)| ext)
+    }
+
+    /// Lookup by uri, whatever id it is negotiated with
+    pub fn ext_by_uri(&self, map: &ExtMap, uri: &str) -> Option<&'a [u8]> {
+        self.ext_by_id(map.id(uri)?)
+    }
+
+    /// Typed lookup, e.g. `packet.ext_value::<AudioLevelValue>(&map)`
+    pub fn ext_value<T: ExtValue<'a>>(&self, map: &ExtMap) -> Result<Option<T>, RtpError> {
+        match self.ext_by_uri(map, T::URI) {
+            Some(ext) => T::parse(ext).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Format and range of extension elements in packet buf
+    pub(super) fn extension_range(&self) -> Option<(ExtFormat, Range<usize>)> {
+        let header = self.header();
+        if header.extension_flag() {
+            let offset = header.header_end();
+            let (ext_fmt, _ext_buf) = self.extension_uncheck(offset);
+            let start = offset + Self::EXTENSION_HEADER_LEN;
+            Some((ExtFormat::from_num_uncheck(ext_fmt), start..start + self.extension_len()))
+        } else {
+            None
+        }
+    }
+    
     fn extension(&self) -> Option<(u16, &'a [u8])> {
         let header = self.header();
         if header.extension_flag() {
@@ -380,6 +413,112 @@ impl<'a> fmt::Display for RefRtpPacket<'a> {
 
 
 
+/// Mutable view for rewriting a parsed packet in place
+pub struct RefMutRtpPacket<'a> {
+    buf: &'a mut [u8],
+}
+
+impl<'a> TryFrom<&'a mut [u8]> for RefMutRtpPacket<'a> {
+    type Error = RtpError;
+
+    fn try_from(buf: &'a mut [u8]) -> Result<Self, Self::Error> {
+        RefRtpPacket::parse(buf)?;
+        Ok(Self { buf })
+    }
+}
+
+impl<'a> RefMutRtpPacket<'a> {
+
+    #[inline]
+    pub fn as_ref(&self) -> RefRtpPacket<'_> {
+        RefRtpPacket::uncheck(self.buf)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buf
+    }
+
+    #[inline]
+    pub fn set_mark_flag(&mut self, mark_flag: bool) -> &mut Self {
+        if mark_flag {
+            self.buf[1] |= 0b1000_0000;
+        } else {
+            self.buf[1] &= 0b0111_1111;
+        }
+        self
+    }
+
+    #[inline]
+    pub fn set_payload_type(&mut self, payload_type: u8) -> &mut Self {
+        assert!(payload_type < 128, "invalid RTP payload type [{payload_type}]");
+        self.buf[1] = (self.buf[1] & 0b1000_0000) | payload_type;
+        self
+    }
+
+    #[inline]
+    pub fn set_seq(&mut self, seq: Seq) -> &mut Self {
+        self.buf[2..4].copy_from_slice(&seq.0.to_be_bytes());
+        self
+    }
+
+    #[inline]
+    pub fn set_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
+        self.buf[4..8].copy_from_slice(&timestamp.0.to_be_bytes());
+        self
+    }
+
+    #[inline]
+    pub fn set_ssrc(&mut self, ssrc: u32) -> &mut Self {
+        self.buf[8..12].copy_from_slice(&ssrc.to_be_bytes());
+        self
+    }
+
+    /// index must be less than csrc count
+    pub fn set_csrc(&mut self, index: usize, csrc: u32) -> &mut Self {
+        let count = self.as_ref().header().csrc_count() as usize;
+        assert!(index < count, "RTP csrc index [{index}] out of count [{count}]");
+
+        let offset = RefRtpHeader::MIN_LEN + 4 * index;
+        self.buf[offset..offset + 4].copy_from_slice(&csrc.to_be_bytes());
+        self
+    }
+
+    /// Value of the first element with id
+    pub fn ext_mut(&mut self, id: u8) -> Option<&mut [u8]> {
+        let (ext_fmt, range) = self.as_ref().extension_range()?;
+        let elem = ext_fmt.elem_iter(&self.buf[range.clone()]).find(|x| x.id == id)?;
+
+        let start = range.start + elem.offset + elem.header_len;
+        Some(&mut self.buf[start..start + elem.body_len])
+    }
+
+    /// Overwrite value of an existing element, length must be the same
+    pub fn replace_ext(&mut self, id: u8, value: &[u8]) -> Result<(), RtpError> {
+        let ext = self.ext_mut(id).ok_or(RtpError::ExtNotFound(id))?;
+        if ext.len() != value.len() {
+            return Err(RtpError::InvalidExtLength(value.len()));
+        }
+        ext.copy_from_slice(value);
+        Ok(())
+    }
+
+    pub fn payload_mut(&mut self) -> &mut [u8] {
+        let packet = self.as_ref();
+        let start = packet.payload_offset();
+        let end = self.buf.len() - packet.padding().unwrap_or(0) as usize;
+        &mut self.buf[start..end]
+    }
+}
+
+impl<'a> fmt::Debug for RefMutRtpPacket<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Debug::fmt(&self.as_ref(), f)
+    }
+}
+
+
+
 pub struct RtpBuilder<'a> {
     buf: &'a mut [u8],
     len: usize,
@@ -431,6 +570,7 @@ impl<'a> RtpBuilder<'a> {
         self.len += 4;
 
         ExtBuilder {
+            fmt: ext_fmt,
             func: ext_fmt.build_fn(),
             // owner: self,
             total_len: self.len,
@@ -454,6 +594,7 @@ impl<'a> RtpBuilder<'a> {
 
 
 pub struct ExtBuilder<'a> {
+    fmt: ExtFormat,
     func: WriteExtFns,
     // owner: &'a mut RtpBuilder<'a>,
     buf: &'a mut [u8],
@@ -463,6 +604,11 @@ pub struct ExtBuilder<'a> {
 
 impl<'a> ExtBuilder<'a> {
 
+    #[inline]
+    pub fn format(&self) -> ExtFormat {
+        self.fmt
+    }
+
     #[inline]
     pub fn write_ext(&mut self, id: u8, ext: &[u8]) {
         let buf = &mut self.buf[self.total_len..];
@@ -477,6 +623,27 @@ impl<'a> ExtBuilder<'a> {
         // self.total_len += len;
     }
 
+    /// Checked write_ext, error instead of panic if the element is invalid or doesn't fit buf with padding
+    pub fn try_write_ext(&mut self, id: u8, ext: &[u8]) -> Result<(), RtpError> {
+        if id == 0 || id > self.fmt.max_id() {
+            return Err(RtpError::InvalidExtId(id));
+        }
+        self.fmt.check_len(ext.len())?;
+
+        let body_len = self.total_len - self.offset - 4 + self.fmt.elem_header_len() + ext.len();
+        let expect = self.offset + 4 + body_len.div_ceil(4) * 4;
+        if expect > self.buf.len() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect,
+                actual: self.buf.len(),
+                origin: "RTP header extension",
+            });
+        }
+
+        self.write_ext(id, ext);
+        Ok(())
+    }
+
     #[inline]
     pub fn ext<'b>(&'b mut self, id: u8) -> ExtItemBuilder<'b, 'a> {
         let offset = self.total_len;
@@ -502,7 +669,7 @@ impl<'a> ExtBuilder<'a> {
         build_payload(self.buf, self.total_len, payload, padding)
     }
 
-    fn payload_builder(mut self) -> PayloadBuilder<'a> {
+    pub fn payload_builder(mut self) -> PayloadBuilder<'a> {
         self.finish();
         PayloadBuilder {
             buf: self.buf,
@@ -554,6 +721,12 @@ impl<'a, 'b> Drop for ExtItemBuilder<'a, 'b> {
 }
 
 impl<'a, 'b> ExtItemBuilder<'a, 'b> {
+    pub fn write_u8(&mut self, value: u8) -> &mut Self {
+        self.tail_buf().put_u8(value);
+        self.item_body_len += 1;
+        self
+    }
+
     pub fn write_u16(&mut self, value: u16) -> &mut Self {
         self.tail_buf().put_u16(value);
         self.item_body_len += 2;
@@ -594,6 +767,12 @@ pub struct PayloadBuilder<'a> {
 } 
 
 impl<'a> PayloadBuilder<'a> {
+    /// Length of header and extensions written so far
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        self.total_len
+    }
+
     #[inline]
     pub fn payload(self, payload: &[u8], padding: bool) -> usize {
         build_payload(self.buf, self.total_len, payload, padding)
@@ -695,7 +874,44 @@ where
 mod test {
     use crate::rtp::{extension::ExtFormat, Seq, Timestamp};
 
-    use super::{RefRtpPacket, RtpBuilder, PayloadBuilder};
+    use crate::rtp::error::RtpError;
+
+    use super::{RefMutRtpPacket, RefRtpPacket, RtpBuilder, PayloadBuilder};
+
+    #[test]
+    fn test_mut_rtp() {
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtpBuilder::from_basic(&mut buf, false, 96, Seq(1), Timestamp(90), 1111, [2222].into_iter())
+            .extension(ExtFormat::OneByte);
+        builder.write_ext(1, &[0x01, 0x02]);
+        builder.write_ext(2, &[0x03]);
+        let len = builder.payload(&[1, 2, 3, 4], true);
+
+        let mut packet = RefMutRtpPacket::try_from(&mut buf[..len]).unwrap();
+        packet
+        .set_mark_flag(true)
+        .set_payload_type(100)
+        .set_seq(Seq(7))
+        .set_timestamp(Timestamp(180))
+        .set_ssrc(3333)
+        .set_csrc(0, 4444);
+        packet.replace_ext(1, &[0x0A, 0x0B]).unwrap();
+        assert!(matches!(packet.replace_ext(2, &[0, 0]), Err(RtpError::InvalidExtLength(2))));
+        assert!(matches!(packet.replace_ext(3, &[0]), Err(RtpError::ExtNotFound(3))));
+        packet.payload_mut()[0] = 9;
+
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+        let header = packet.header();
+        assert!(header.mark_flag());
+        assert_eq!(header.payload_type(), 100);
+        assert_eq!(header.seq(), Seq(7));
+        assert_eq!(header.timestamp(), Timestamp(180));
+        assert_eq!(header.ssrc(), 3333);
+        assert!(packet.csrc_iter().eq([4444]));
+        assert_eq!(packet.ext_by_id(1), Some(&[0x0A, 0x0B][..]));
+        assert_eq!(packet.ext_by_id(2), Some(&[0x03][..]));
+        assert_eq!(packet.payload(), &[9, 2, 3, 4]);
+    }
 
     #[derive(Debug, Clone)]
     struct Case {
@@ -883,4 +1099,22 @@ mod test {
 
     }
 
+    #[test]
+    fn test_try_write_ext() {
+        let mut buf = vec![0_u8; 24];
+        let mut builder = RtpBuilder::from_basic(&mut buf, false, 96, Seq(1), Timestamp(0), 1111, [].into_iter())
+            .extension(ExtFormat::OneByte);
+
+        // 12 bytes header, 4 bytes extension header, element padded to 4 bytes
+        builder.try_write_ext(1, &[1, 2, 3]).unwrap();
+        assert!(matches!(builder.try_write_ext(2, &[1, 2, 3, 4]), Err(RtpError::NotEnoughBuffer { expect: 28, actual: 24, .. })));
+        assert!(matches!(builder.try_write_ext(15, &[1]), Err(RtpError::InvalidExtId(15))));
+        assert!(matches!(builder.try_write_ext(2, &[]), Err(RtpError::InvalidExtLength(0))));
+
+        let len = builder.payload(&[], false);
+        assert_eq!(len, 20);
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+        assert!(packet.extension_iter().unwrap().eq([(1, &[1, 2, 3][..])]));
+    }
+
 }
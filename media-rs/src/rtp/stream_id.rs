@@ -0,0 +1,138 @@
+//! https://datatracker.ietf.org/doc/html/rfc8843#section-15
+//! https://datatracker.ietf.org/doc/html/rfc8852#section-3
+//!
+
+use super::{error::RtpError, extmap::ExtValue, ExtBuilder};
+
+
+macro_rules! define_str_ext {
+    ($(#[$meta:meta])* $name:ident, $uri:expr, $valid_char:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name<'a>(pub &'a str);
+
+        impl<'a> $name<'a> {
+            pub const URI: &'static str = $uri;
+
+            pub fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+                if data.is_empty() {
+                    return Err(RtpError::NotEnoughBuffer {
+                        expect: 1,
+                        actual: 0,
+                        origin: concat!(stringify!($name), " length"),
+                    });
+                }
+
+                let valid_char: fn(u8) -> bool = $valid_char;
+                if !data.iter().all(|c| valid_char(*c)) {
+                    return Err(RtpError::InvalidExtValue);
+                }
+
+                std::str::from_utf8(data)
+                    .map(Self)
+                    .map_err(|_e| RtpError::InvalidExtValue)
+            }
+
+            #[inline]
+            pub fn as_bytes(&self) -> &'a [u8] {
+                self.0.as_bytes()
+            }
+
+            /// Write as element id, value is validated as parse does and length is checked against builder's format
+            pub fn build(&self, builder: &mut ExtBuilder<'_>, id: u8) -> Result<(), RtpError> {
+                Self::parse(self.as_bytes())?;
+                builder.format().check_len(self.0.len())?;
+                builder.ext(id).write_slice(self.as_bytes());
+                Ok(())
+            }
+        }
+
+        impl<'a> ExtValue<'a> for $name<'a> {
+            const URI: &'static str = Self::URI;
+
+            fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+                Self::parse(data)
+            }
+        }
+    };
+}
+
+#[inline]
+fn is_token_char(c: u8) -> bool {
+    // token of RFC 4566
+    c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`{|}~".contains(&c)
+}
+
+#[inline]
+fn is_rid_char(c: u8) -> bool {
+    // rid-id of RFC 8851
+    c.is_ascii_alphanumeric() || c == b'-' || c == b'_'
+}
+
+define_str_ext!(
+    /// Media identification of BUNDLE
+    MidValue, "urn:ietf:params:rtp-hdrext:sdes:mid", is_token_char
+);
+
+define_str_ext!(
+    /// Simulcast stream id
+    RidValue, "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id", is_rid_char
+);
+
+define_str_ext!(
+    /// Stream id which a retransmission or FEC stream repairs
+    RepairedRidValue, "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id", is_rid_char
+);
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, extension::ExtFormat, extmap::ExtMap, RefRtpPacket, RtpBuilder, Seq, Timestamp};
+
+    use super::{MidValue, RepairedRidValue, RidValue};
+
+    #[test]
+    fn test_build_stream_id() {
+        let mut map = ExtMap::new();
+        map.insert(1, MidValue::URI).unwrap();
+        map.insert(2, RidValue::URI).unwrap();
+        map.insert(3, RepairedRidValue::URI).unwrap();
+
+        let mut buf = vec![0_u8; 1500];
+        let mut builder = RtpBuilder::from_basic(&mut buf, false, 96, Seq(1), Timestamp(0), 1111, [].into_iter())
+            .extension(ExtFormat::OneByte);
+        MidValue("0").build(&mut builder, 1).unwrap();
+        RidValue("hi-res").build(&mut builder, 2).unwrap();
+        assert!(matches!(
+            RepairedRidValue("a_very_long_rid_id").build(&mut builder, 3),
+            Err(RtpError::InvalidExtLength(18))
+        ));
+        assert!(RepairedRidValue("").build(&mut builder, 3).is_err());
+        let len = builder.payload(&[1, 2, 3], false);
+
+        let packet = RefRtpPacket::parse(&buf[..len]).unwrap();
+        assert_eq!(packet.ext_value::<MidValue>(&map).unwrap(), Some(MidValue("0")));
+        assert_eq!(packet.ext_value::<RidValue>(&map).unwrap(), Some(RidValue("hi-res")));
+        assert_eq!(packet.ext_value::<RepairedRidValue>(&map).unwrap(), None);
+
+        // rejected as parse does, even in two-byte format
+        let mut builder = RtpBuilder::from_basic(&mut buf, false, 96, Seq(1), Timestamp(0), 1111, [].into_iter())
+            .extension(ExtFormat::TwoByte);
+        assert!(MidValue("").build(&mut builder, 1).is_err());
+        assert!(matches!(RidValue("a b").build(&mut builder, 2), Err(RtpError::InvalidExtValue)));
+        assert!(matches!(RidValue("hi.res").build(&mut builder, 2), Err(RtpError::InvalidExtValue)));
+        assert!(MidValue("a.b").build(&mut builder, 1).is_ok());
+
+        assert!(RidValue::parse(b"a b").is_err());
+        assert!(MidValue::parse(b"").is_err());
+    }
+
+    #[test]
+    fn test_twobyte_len() {
+        assert!(ExtFormat::TwoByte.check_len(0).is_ok());
+        assert!(ExtFormat::TwoByte.check_len(255).is_ok());
+        assert!(ExtFormat::TwoByte.check_len(256).is_err());
+        assert!(ExtFormat::OneByte.check_len(16).is_ok());
+        assert!(ExtFormat::OneByte.check_len(0).is_err());
+    }
+}
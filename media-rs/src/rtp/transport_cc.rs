@@ -0,0 +1,65 @@
+//! https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01#section-2
+//!
+
+use super::{error::RtpError, extmap::ExtValue, Seq};
+
+
+/// Transport-wide sequence number, shared by all streams of a transport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportCcValue {
+    pub seq: Seq,
+}
+
+impl TransportCcValue {
+    pub const URI: &'static str = "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+
+    pub const LEN: usize = 2;
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.len() < Self::LEN {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: Self::LEN,
+                actual: data.len(),
+                origin: "Transport cc value length",
+            });
+        }
+
+        Ok(Self {
+            seq: Seq(u16::from_be_bytes([data[0], data[1]])),
+        })
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 2] {
+        self.seq.0.to_be_bytes()
+    }
+}
+
+impl<'a> ExtValue<'a> for TransportCcValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::{error::RtpError, Seq};
+
+    use super::TransportCcValue;
+
+    #[test]
+    fn test() {
+        let v = TransportCcValue { seq: Seq(0xABCD) };
+        assert_eq!(v.to_bytes(), [0xAB, 0xCD]);
+        assert_eq!(TransportCcValue::parse(&[0xAB, 0xCD]).unwrap(), v);
+        assert_eq!(TransportCcValue { seq: Seq(1) }.to_bytes(), [0x00, 0x01]);
+
+        assert!(matches!(
+            TransportCcValue::parse(&[0xAB]),
+            Err(RtpError::NotEnoughBuffer { expect: 2, actual: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+//! http://www.webrtc.org/experiments/rtp-hdrext/video-content-type
+//!
+
+use super::{error::RtpError, extmap::ExtValue};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VideoContentTypeValue {
+    pub screenshare: bool,
+}
+
+impl VideoContentTypeValue {
+    pub const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/video-content-type";
+
+    const SCREENSHARE: u8 = 0x01;
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 1,
+                actual: data.len(),
+                origin: "Video content type value length",
+            });
+        }
+
+        Ok(Self {
+            screenshare: (data[0] & Self::SCREENSHARE) != 0,
+        })
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 1] {
+        if self.screenshare {
+            [ Self::SCREENSHARE ]
+        } else {
+            [ 0 ]
+        }
+    }
+}
+
+impl<'a> ExtValue<'a> for VideoContentTypeValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::rtp::error::RtpError;
+
+    use super::VideoContentTypeValue;
+
+    #[test]
+    fn test() {
+        let screenshare = VideoContentTypeValue { screenshare: true };
+        assert_eq!(screenshare.to_bytes(), [0x01]);
+        assert_eq!(VideoContentTypeValue::parse(&[0x01]).unwrap(), screenshare);
+
+        let unspecified = VideoContentTypeValue::default();
+        assert_eq!(unspecified.to_bytes(), [0x00]);
+        assert_eq!(VideoContentTypeValue::parse(&[0x00]).unwrap(), unspecified);
+
+        // reserved bits are ignored
+        assert_eq!(VideoContentTypeValue::parse(&[0xFE]).unwrap(), unspecified);
+        assert_eq!(VideoContentTypeValue::parse(&[0xFF]).unwrap(), screenshare);
+
+        assert!(matches!(
+            VideoContentTypeValue::parse(&[]),
+            Err(RtpError::NotEnoughBuffer { expect: 1, actual: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! https://www.etsi.org/deliver/etsi_ts/126100_126199/126114/16.07.00_60/ts_126114v160700p.pdf
+//! 7.4.5 Coordination of Video Orientation
+//!
+
+use super::{error::RtpError, extmap::ExtValue};
+
+
+/// Counter clockwise rotation applied by sender
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoRotation {
+    #[default]
+    Deg0 = 0,
+    Deg90 = 1,
+    Deg180 = 2,
+    Deg270 = 3,
+}
+
+impl VideoRotation {
+    fn from_bits(v: u8) -> Self {
+        match v & 0x03 {
+            0 => Self::Deg0,
+            1 => Self::Deg90,
+            2 => Self::Deg180,
+            _ => Self::Deg270,
+        }
+    }
+
+    #[inline]
+    pub fn degrees(&self) -> u16 {
+        *self as u16 * 90
+    }
+}
+
+/*
+    0 1 2 3 4 5 6 7
+   +-+-+-+-+-+-+-+-+
+   |0 0 0 0 C F R R|
+   +-+-+-+-+-+-+-+-+
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VideoOrientationValue {
+    /// false: front-facing camera
+    pub back_camera: bool,
+    /// horizontal flip
+    pub flip: bool,
+    pub rotation: VideoRotation,
+}
+
+impl VideoOrientationValue {
+    pub const URI: &'static str = "urn:3gpp:video-orientation";
+
+    pub fn parse(data: &[u8]) -> Result<Self, RtpError> {
+        if data.is_empty() {
+            return Err(RtpError::NotEnoughBuffer {
+                expect: 1,
+                actual: data.len(),
+                origin: "Video orientation value length",
+            });
+        }
+
+        let b = data[0];
+        Ok(Self {
+            back_camera: (b & 0b0000_1000) != 0,
+            flip: (b & 0b0000_0100) != 0,
+            rotation: VideoRotation::from_bits(b),
+        })
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 1] {
+        let mut b = self.rotation as u8;
+        if self.back_camera {
+            b |= 0b0000_1000;
+        }
+        if self.flip {
+            b |= 0b0000_0100;
+        }
+        [ b ]
+    }
+}
+
+impl<'a> ExtValue<'a> for VideoOrientationValue {
+    const URI: &'static str = Self::URI;
+
+    fn parse(data: &'a [u8]) -> Result<Self, RtpError> {
+        Self::parse(data)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let v = VideoOrientationValue::parse(&[0b0000_1101]).unwrap();
+        assert!(v.back_camera);
+        assert!(v.flip);
+        assert_eq!(v.rotation, VideoRotation::Deg90);
+        assert_eq!(v.rotation.degrees(), 90);
+        assert_eq!(v.to_bytes(), [0b0000_1101]);
+
+        // reserved bits are ignored
+        let v = VideoOrientationValue::parse(&[0b1111_0011]).unwrap();
+        assert_eq!(v.to_bytes(), [0b0000_0011]);
+    }
+}